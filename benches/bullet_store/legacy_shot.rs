@@ -0,0 +1,205 @@
+use poi_project::{SCREEN_HEIGHT, SCREEN_WIDTH, STAGE_DOWN, STAGE_LEFT, STAGE_RIGHT, STAGE_UP};
+
+// BulletStore以前の敵弾（baselineのlib.rs, shot_type.rsの写し）
+// 1発ずつActorとしてVec<Actor>に入れ, movingの反映, 移動, retainで消す
+// 分裂はfireflowerと同じく, 毎count全ての弾のmemo（String）を見て子弾を作る
+//
+// ベンチマークでBulletStoreと比べるためだけのもので, ゲームでは使わない
+
+const RELATIVE_Y: f32 = SCREEN_HEIGHT / 960.0;
+const RELATIVE_X: f32 = SCREEN_WIDTH / 1280.0;
+
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Clone)]
+enum ActorType {
+	Player,
+	Enemy,
+	Boss,
+	PlShot,
+	EnShot,
+	Effect,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovingElement {
+	count: u32,
+	// イベントを起こすカウント数
+	accel: [f32; 2],
+	// 加速度 [角度の値, スカラ値]
+	shot_type: String,
+	// 放つShotの種類
+}
+
+impl MovingElement {
+	pub fn new(count: u32, accel: [f32; 2], shot_type: &str) -> MovingElement {
+		MovingElement {
+			count,
+			accel,
+			shot_type: shot_type.to_string(),
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Actor {
+	#[allow(dead_code)]
+	actor_type: ActorType,
+	// 弾では使わない（大きさを揃えるため）
+	point: [f32; 2],
+	// 位置 [x, y]
+	velocity: [f32; 2],
+	// [Angle(0.0 <= x < 2.0, 真下が0, 右回り), scalar]
+	accel: [f32; 2],
+	// 加速度
+	bbox_size: f32,
+	// 当たり判定の半径
+	life: f32,
+	// 1.0と0.0でboolのように使う
+	moving: Vec<MovingElement>,
+	// 動作の記録
+	count: u32,
+	// 経過countメモ
+	memo: String,
+	// メモ用
+}
+
+impl Default for Actor {
+	fn default() -> Actor {
+		Actor {
+			actor_type: ActorType::EnShot,
+			point: [0.0; 2],
+			velocity: [0.0; 2],
+			accel: [0.0; 2],
+			bbox_size: 10.0,
+			life: 1.0,
+			moving: Vec::new(),
+			count: 0,
+			memo: String::new(),
+		}
+	}
+}
+
+impl Actor {
+	pub fn new(point: [f32; 2], velocity: [f32; 2], accel: [f32; 2], moving: Vec<MovingElement>, memo: &str) -> Actor {
+		Actor {
+			point,
+			velocity,
+			accel,
+			moving,
+			memo: memo.to_owned(),
+			..Default::default()
+		}
+	}
+
+	fn update_point_shot(actor: &mut Actor, dt: f32) {
+		use std::f32::consts::PI;
+
+		let mergin_x = 30.0 * RELATIVE_X;
+		let mergin_y = 30.0 * RELATIVE_Y;
+		let s_up = STAGE_UP - mergin_y;
+		let s_down = STAGE_DOWN + mergin_y;
+		let s_left = STAGE_LEFT - mergin_x;
+		let s_right = STAGE_RIGHT + mergin_x;
+
+		let scalar = actor.velocity[1];
+		let ragian = actor.velocity[0] * PI;
+		let mut x_vel = scalar * ragian.sin();
+		let mut y_vel = scalar * ragian.cos();
+
+		if (actor.point[0] < s_left && x_vel < 0.0) || (actor.point[0] > s_right && x_vel > 0.0) {
+			x_vel = 0.0;
+			actor.life = 0.0;
+		}
+		if (actor.point[1] < s_up && y_vel < 0.0) || (actor.point[1] > s_down && y_vel > 0.0) {
+			y_vel = 0.0;
+			actor.life = 0.0;
+		}
+
+		actor.velocity[0] += actor.accel[0] * dt;
+		actor.velocity[1] += actor.accel[1] * dt;
+		actor.point[0] += x_vel * RELATIVE_X * dt;
+		actor.point[1] += y_vel * RELATIVE_Y * dt;
+	}
+}
+
+#[derive(Debug, Default)]
+pub struct LegacyShots {
+	shots: Vec<Actor>,
+}
+
+impl LegacyShots {
+	pub fn len(&self) -> usize {
+		self.shots.len()
+	}
+
+	pub fn push(&mut self, shot: Actor) {
+		self.shots.push(shot);
+	}
+
+	// fireflowerの分裂（originは60countで8発, splitは60countで12発に分かれて消える）
+	pub fn split(&mut self) {
+		let mut push_enshot_buf: Vec<Actor> = Vec::new();
+		for es in &mut self.shots {
+			match es.memo.as_str() {
+				"origin" if es.count == 60 => {
+					es.memo = "origin_d".to_owned();
+					let mut push_shot = Actor {
+						point: es.point,
+						velocity: es.velocity,
+						memo: "split".to_owned(),
+						..Default::default()
+					};
+					for i in 0..4 {
+						push_shot.velocity[1] = 250.0;
+						push_shot.velocity[0] += 0.18 * i as f32;
+						push_enshot_buf.push(push_shot.clone());
+						push_shot.velocity[0] -= 0.18 * i as f32;
+						push_enshot_buf.push(push_shot.clone());
+					}
+				},
+				"split" if es.count == 60 => {
+					es.memo = "split_d".to_owned();
+					let mut push_shot = Actor {
+						point: es.point,
+						velocity: es.velocity,
+						memo: "".to_owned(),
+						..Default::default()
+					};
+					for i in 1..7 {
+						push_shot.velocity[1] = 250.0;
+						push_shot.velocity[0] += 0.18 * i as f32;
+						push_enshot_buf.push(push_shot.clone());
+						push_shot.velocity[0] -= 0.18 * i as f32;
+						push_enshot_buf.push(push_shot.clone());
+					}
+				},
+				_ => (),
+			}
+			if es.memo == "origin_d" || es.memo == "split_d" {
+				es.life = 0.0;
+			}
+		}
+		for peb in push_enshot_buf {
+			self.shots.push(peb);
+		}
+	}
+
+	// 1count分動かし, 画面外に出た弾とhitがtrueの弾を消す
+	// hit: [位置, 当たり判定の半径]
+	pub fn update<F: Fn([f32; 2], f32) -> bool>(&mut self, hit: F, dt: f32) {
+		for es in &mut self.shots {
+			es.count += 1;
+			for esm in es.moving.iter() {
+				if esm.count == es.count {
+					es.accel = esm.accel;
+					es.memo = esm.shot_type.clone();
+				}
+			}
+			Actor::update_point_shot(es, dt);
+			if hit(es.point, es.bbox_size) {
+				es.life = 0.0;
+			}
+		}
+		self.shots.retain(|s| s.life > 0.0);
+	}
+}
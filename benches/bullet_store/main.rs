@@ -0,0 +1,202 @@
+#[macro_use]
+extern crate criterion;
+extern crate poi_project;
+
+use criterion::{Criterion, ParameterizedBenchmark};
+
+use poi_project::bullet_store::{Bullet, BulletStore};
+use poi_project::bullet_type::{BulletColor, BulletKind, BulletStyle};
+use poi_project::shot_behavior::Behavior;
+use poi_project::shot_spawn::{split_angles, ChildPattern, Spawn, Trigger};
+
+use std::rc::Rc;
+
+mod legacy_shot;
+
+use legacy_shot::{Actor, LegacyShots, MovingElement};
+
+// 敵弾1count分の更新（移動, 当たり判定, 消えた弾の回収, 補充）の速さ
+// BulletStore と 変更前の Vec<Actor>（legacy_shot.rs）を弾数毎に比べる
+//   enshot_tick: 真っ直ぐ飛ぶ弾と, 4発に1発carpet_bombのように曲がる弾
+//   enshot_spawn_tick: fireflowerのように60countで分裂する弾（8発 -> 64発 -> 768発）
+//     弾の数がnより少なければoriginを撃つので, 分裂した分だけnより多くなる
+//
+// cargo bench --bench bullet_store
+
+const DT: f32 = 1.0 / 60.0;
+const CENTER: [f32; 2] = [330.0, 300.0];
+const PLAYER: [f32; 2] = [330.0, 600.0];
+const PLAYER_BBOX: f32 = 5.0;
+const SHOT_BBOX: f32 = 10.0;
+// 元のActorと同じ当たり判定の半径
+const VOLLEY: u32 = 8;
+// fireflowerの1回のoriginの数
+
+// 同じ乱数列で両方を比べる
+struct Lcg(u32);
+
+impl Lcg {
+	fn next(&mut self) -> f32 {
+		self.0 = self.0.wrapping_mul(1664525).wrapping_add(1013904223);
+		(self.0 >> 8) as f32 / (1 << 24) as f32
+	}
+}
+
+fn hit(point: [f32; 2], bbox_size: f32) -> bool {
+	let x = point[0] - PLAYER[0];
+	let y = point[1] - PLAYER[1];
+	let r = PLAYER_BBOX + bbox_size;
+	x * x + y * y < r * r
+}
+
+fn velocity(rng: &mut Lcg) -> [f32; 2] {
+	[2.0 * rng.next(), 50.0 + 100.0 * rng.next()]
+}
+
+fn legacy_tick(shots: &mut LegacyShots, rng: &mut Lcg, n: usize) {
+	shots.update(hit, DT);
+	let mut i = shots.len();
+	while shots.len() < n {
+		let moving = if i % 4 == 0 {
+			vec![
+				MovingElement::new(30, [0.7, 0.0], "origin"),
+				MovingElement::new(120, [0.0, 0.0], ""),
+			]
+		} else {
+			Vec::new()
+		};
+		shots.push(Actor::new(CENTER, velocity(rng), [0.0; 2], moving, ""));
+		i += 1;
+	}
+}
+
+fn legacy_spawn_tick(shots: &mut LegacyShots, rng: &mut Lcg, n: usize) {
+	shots.split();
+	shots.update(hit, DT);
+	if shots.len() < n {
+		let angle = rng.next();
+		for i in 0..VOLLEY {
+			let velocity = [(i as f32 + 2.0 * angle) * 2.0 / VOLLEY as f32, 300.0];
+			shots.push(Actor::new(CENTER, velocity, [0.0, -300.0], Vec::new(), "origin"));
+		}
+	}
+}
+
+fn store_tick(store: &mut BulletStore, buf: &mut Vec<Bullet>) {
+	store.update(PLAYER, &[], buf, DT);
+	for es in store.iter_mut() {
+		if hit(es.point, SHOT_BBOX) {
+			es.alive = false;
+		}
+	}
+	store.sweep();
+	store.append(buf);
+}
+
+fn store_plain_tick(store: &mut BulletStore, buf: &mut Vec<Bullet>, rng: &mut Lcg, n: usize) {
+	store_tick(store, buf);
+	let mut i = store.len();
+	while store.len() < n {
+		let mut bullet = Bullet::new(CENTER, velocity(rng), BulletStyle::new(BulletKind::Pellet, BulletColor::Red));
+		if i % 4 == 0 {
+			bullet.behavior = Behavior::Curve { from: 30, until: 120, turn: 0.7 };
+		}
+		store.push(bullet);
+		i += 1;
+	}
+}
+
+// fireflowerと同じ分裂
+fn fireflower_spawns() -> Rc<Vec<Spawn>> {
+	let last = Rc::new(ChildPattern {
+		angles: split_angles(0.18, 1, 7),
+		speed: 250.0,
+		accel: [0.0; 2],
+		style: BulletStyle::new(BulletKind::Pellet, BulletColor::Yellow),
+		behavior: Behavior::None,
+		children: None,
+	});
+	let split = Rc::new(ChildPattern {
+		angles: split_angles(0.18, 0, 4),
+		speed: 250.0,
+		accel: [0.0; 2],
+		style: BulletStyle::new(BulletKind::Star, BulletColor::Orange),
+		behavior: Behavior::None,
+		children: Some(Rc::new(vec![Spawn::new(Trigger::At(60), last, true)])),
+	});
+	Rc::new(vec![Spawn::new(Trigger::At(60), split, true)])
+}
+
+fn store_spawn_tick(store: &mut BulletStore, buf: &mut Vec<Bullet>, spawns: &Rc<Vec<Spawn>>, rng: &mut Lcg, n: usize) {
+	store_tick(store, buf);
+	if store.len() < n {
+		let angle = rng.next();
+		for i in 0..VOLLEY {
+			store.push(Bullet {
+				point: CENTER,
+				velocity: [(i as f32 + 2.0 * angle) * 2.0 / VOLLEY as f32, 300.0],
+				accel: [0.0, -300.0],
+				style: BulletStyle::new(BulletKind::Orb, BulletColor::Red),
+				spawns: Some(spawns.clone()),
+				..Default::default()
+			});
+		}
+	}
+}
+
+fn bench_tick(c: &mut Criterion) {
+	let bench = ParameterizedBenchmark::new(
+		"vec_actor",
+		|b, &n| {
+			let mut rng = Lcg(1);
+			let mut shots = LegacyShots::default();
+			legacy_tick(&mut shots, &mut rng, n);
+			b.iter(|| legacy_tick(&mut shots, &mut rng, n));
+		},
+		vec![1000, 10000, 50000],
+	).with_function(
+		"bullet_store",
+		|b, &n| {
+			let mut rng = Lcg(1);
+			let mut store = BulletStore::with_capacity(n);
+			let mut buf = Vec::new();
+			store_plain_tick(&mut store, &mut buf, &mut rng, n);
+			b.iter(|| store_plain_tick(&mut store, &mut buf, &mut rng, n));
+		},
+	);
+	c.bench("enshot_tick", bench);
+}
+
+// 分裂が続いて弾の数が落ち着くまで進めてから測る
+const WARM_UP: u32 = 300;
+
+fn bench_spawn_tick(c: &mut Criterion) {
+	let bench = ParameterizedBenchmark::new(
+		"vec_actor",
+		|b, &n| {
+			let mut rng = Lcg(1);
+			let mut shots = LegacyShots::default();
+			for _ in 0..WARM_UP {
+				legacy_spawn_tick(&mut shots, &mut rng, n);
+			}
+			b.iter(|| legacy_spawn_tick(&mut shots, &mut rng, n));
+		},
+		vec![1000, 10000, 50000],
+	).with_function(
+		"bullet_store",
+		|b, &n| {
+			let mut rng = Lcg(1);
+			let mut store = BulletStore::with_capacity(n);
+			let mut buf = Vec::new();
+			let spawns = fireflower_spawns();
+			for _ in 0..WARM_UP {
+				store_spawn_tick(&mut store, &mut buf, &spawns, &mut rng, n);
+			}
+			b.iter(|| store_spawn_tick(&mut store, &mut buf, &spawns, &mut rng, n));
+		},
+	);
+	c.bench("enshot_spawn_tick", bench);
+}
+
+criterion_group!(benches, bench_tick, bench_spawn_tick);
+criterion_main!(benches);
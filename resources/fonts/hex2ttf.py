@@ -0,0 +1,217 @@
+#!/usr/bin/env python3
+# GNU Unifontの.hex（16px高のビットマップ）をTrueTypeに変換する
+# 1ドットを64unitの正方形にし, 横に続くドットと同じ形の上下の行をまとめた長方形をglyphにする
+#
+# python3 hex2ttf.py unifont-jp.hex unifont-jp.ttf
+
+import struct
+import sys
+
+UNIT = 64
+EM = 16 * UNIT
+ASCENT = 14 * UNIT
+DESCENT = 2 * UNIT
+FAMILY = "Unifont JP"
+VERSION = "Version 1.0"
+NOTICE = "Converted from GNU Unifont. GPLv2+ with the GNU font embedding exception."
+
+
+def read_hex(path):
+	glyphs = {}
+	for line in open(path, encoding="ascii"):
+		line = line.strip()
+		if not line:
+			continue
+		code, bits = line.split(":")
+		glyphs[int(code, 16)] = bits
+	return glyphs
+
+
+def rows(bits):
+	# 1行 8ドット（半角）か16ドット（全角）
+	width = len(bits) // 4
+	step = width // 4
+	return width, [int(bits[i * step:(i + 1) * step], 16) for i in range(16)]
+
+
+def rects(width, rows):
+	# [x0, x1) の横の並びを行毎に求め, 上の行と同じ並びならつなげる
+	done = []
+	open_runs = {}
+	for y in range(17):
+		runs = set()
+		if y < 16:
+			r = rows[y]
+			x = 0
+			while x < width:
+				if r >> (width - 1 - x) & 1:
+					x0 = x
+					while x < width and r >> (width - 1 - x) & 1:
+						x += 1
+					runs.add((x0, x))
+				else:
+					x += 1
+		for run in list(open_runs):
+			if run not in runs:
+				done.append((run[0], open_runs.pop(run), run[1], y))
+		for run in runs:
+			if run not in open_runs:
+				open_runs[run] = y
+	return done
+
+
+def glyph_data(width, rs):
+	boxes = rects(width, rs)
+	if not boxes:
+		return b"", 0, (0, 0, 0, 0), 0
+	pts = []
+	ends = []
+	for x0, y0, x1, y1 in boxes:
+		# 上から数えたドットを, baselineからのunitに直す（時計回り）
+		left, right = x0 * UNIT, x1 * UNIT
+		top, bottom = ASCENT - y0 * UNIT, ASCENT - y1 * UNIT
+		pts += [(left, bottom), (left, top), (right, top), (right, bottom)]
+		ends.append(len(pts) - 1)
+	xs = [p[0] for p in pts]
+	ys = [p[1] for p in pts]
+	bbox = (min(xs), min(ys), max(xs), max(ys))
+
+	flags = bytearray()
+	xd = bytearray()
+	yd = bytearray()
+	px = py = 0
+	for x, y in pts:
+		f = 0x01
+		dx, dy = x - px, y - py
+		if dx == 0:
+			f |= 0x10
+		elif -255 <= dx <= 255:
+			f |= 0x02 | (0x10 if dx > 0 else 0)
+			xd.append(abs(dx))
+		else:
+			xd += struct.pack(">h", dx)
+		if dy == 0:
+			f |= 0x20
+		elif -255 <= dy <= 255:
+			f |= 0x04 | (0x20 if dy > 0 else 0)
+			yd.append(abs(dy))
+		else:
+			yd += struct.pack(">h", dy)
+		flags.append(f)
+		px, py = x, y
+	data = struct.pack(">hhhhh", len(ends), *bbox)
+	data += b"".join(struct.pack(">H", e) for e in ends)
+	data += struct.pack(">H", 0) + bytes(flags) + bytes(xd) + bytes(yd)
+	if len(data) % 2:
+		data += b"\0"
+	return data, len(pts), bbox, len(ends)
+
+
+def cmap_format4(mapping):
+	codes = sorted(c for c in mapping if c <= 0xFFFF)
+	segs = []
+	for c in codes:
+		if segs and c == segs[-1][1] + 1 and mapping[c] == mapping[segs[-1][1]] + 1:
+			segs[-1][1] = c
+		else:
+			segs.append([c, c])
+	segs.append([0xFFFF, 0xFFFF])
+	n = len(segs)
+	ends = b"".join(struct.pack(">H", e) for s, e in segs)
+	starts = b"".join(struct.pack(">H", s) for s, e in segs)
+	deltas = b"".join(struct.pack(">H", (mapping.get(s, 0) - s) & 0xFFFF if s != 0xFFFF else 1) for s, e in segs)
+	offsets = b"\0\0" * n
+	search = 2 ** (n.bit_length() - 1)
+	body = struct.pack(">HHHH", n * 2, search * 2, search.bit_length() - 1, n * 2 - search * 2)
+	body += ends + b"\0\0" + starts + deltas + offsets
+	sub = struct.pack(">HHH", 4, 6 + len(body), 0) + body
+	return struct.pack(">HH", 0, 1) + struct.pack(">HHI", 3, 1, 12) + sub
+
+
+def name_table():
+	names = [(0, NOTICE), (1, FAMILY), (2, "Regular"), (3, FAMILY + " Regular"), (4, FAMILY), (5, VERSION), (6, "UnifontJP")]
+	strings = b""
+	records = b""
+	for nid, s in names:
+		b = s.encode("utf-16-be")
+		records += struct.pack(">HHHHHH", 3, 1, 0x409, nid, len(b), len(strings))
+		strings += b
+	return struct.pack(">HHH", 0, len(names), 6 + 12 * len(names)) + records + strings
+
+
+def checksum(data):
+	data += b"\0" * (-len(data) % 4)
+	return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def build(glyphs, out):
+	codes = sorted(glyphs)
+	# glyph 0: .notdef（空）
+	glyf = bytearray()
+	loca = [0, 0]
+	hmtx = struct.pack(">Hh", 8 * UNIT, 0)
+	mapping = {}
+	max_pts = max_contours = 0
+	x_min = y_min = 0
+	x_max = y_max = 0
+	for i, c in enumerate(codes):
+		width, rs = rows(glyphs[c])
+		data, n, bbox, contours = glyph_data(width, rs)
+		glyf += data
+		loca.append(len(glyf))
+		hmtx += struct.pack(">Hh", width * UNIT, bbox[0])
+		mapping[c] = i + 1
+		if n:
+			max_pts = max(max_pts, n)
+			max_contours = max(max_contours, contours)
+			x_min, y_min = min(x_min, bbox[0]), min(y_min, bbox[1])
+			x_max, y_max = max(x_max, bbox[2]), max(y_max, bbox[3])
+	num = len(codes) + 1
+
+	tables = {}
+	tables[b"head"] = struct.pack(">IIIIHHqqhhhhHHhhh", 0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0x000B, EM,
+		0, 0, x_min, y_min, x_max, y_max, 0, 8, 2, 1, 0)
+	tables[b"hhea"] = struct.pack(">IhhhHhhhhhhhhhhhH", 0x00010000, ASCENT, -DESCENT, 0, 16 * UNIT,
+		0, 0, 16 * UNIT, 1, 0, 0, 0, 0, 0, 0, 0, num)
+	tables[b"maxp"] = struct.pack(">IHHHHHHHHHHHHHH", 0x00010000, num, max_pts, max_contours, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0)
+	tables[b"hmtx"] = hmtx
+	tables[b"loca"] = b"".join(struct.pack(">I", o) for o in loca)
+	tables[b"glyf"] = bytes(glyf)
+	tables[b"cmap"] = cmap_format4(mapping)
+	tables[b"name"] = name_table()
+	tables[b"post"] = struct.pack(">IIhhIIIII", 0x00030000, 0, -2 * UNIT, UNIT, 0, 0, 0, 0, 0)
+	first = min(codes)
+	last = min(max(codes), 0xFFFF)
+	# version 1: 平均幅, 太さ, 幅, 埋め込み可, 下付き, 上付き, 取り消し線, 分類
+	os2 = struct.pack(">HhHHH", 1, 12 * UNIT, 400, 5, 0)
+	os2 += struct.pack(">hhhh", 10 * UNIT, 10 * UNIT, 0, 2 * UNIT)
+	os2 += struct.pack(">hhhh", 10 * UNIT, 10 * UNIT, 0, 6 * UNIT)
+	os2 += struct.pack(">hhh", UNIT, 4 * UNIT, 0)
+	# panose, Unicodeの範囲, vendor
+	os2 += b"\0" * 10 + b"\0" * 16 + b"UFJP"
+	# Latin 1とJIS
+	os2 += struct.pack(">HHHhhhHHII", 0x40, first, last, ASCENT, -DESCENT, 0, ASCENT, DESCENT, 0x00020001, 0)
+	tables[b"OS/2"] = os2
+
+	tags = sorted(tables)
+	n = len(tags)
+	search = 2 ** (n.bit_length() - 1)
+	header = struct.pack(">IHHHH", 0x00010000, n, search * 16, search.bit_length() - 1, n * 16 - search * 16)
+	offset = 12 + 16 * n
+	directory = b""
+	body = b""
+	for t in tags:
+		data = tables[t]
+		directory += struct.pack(">4sIII", t, checksum(data), offset + len(body), len(data))
+		body += data + b"\0" * (-len(data) % 4)
+	font = bytearray(header + directory + body)
+	# head.checkSumAdjustment
+	pos = 12 + 16 * tags.index(b"head")
+	head_offset = struct.unpack(">I", font[pos + 8:pos + 12])[0]
+	adjust = (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF
+	font[head_offset + 8:head_offset + 12] = struct.pack(">I", adjust)
+	open(out, "wb").write(font)
+
+
+if __name__ == "__main__":
+	build(read_hex(sys.argv[1]), sys.argv[2])
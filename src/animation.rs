@@ -0,0 +1,248 @@
+use ggez::graphics;
+use ggez::GameResult;
+use ggez::error::GameError;
+
+use serde_json;
+
+use std::collections::HashMap;
+
+use load_json;
+
+// sprites.json の読み込みとアニメーションの再生
+// sheet: 1枚の画像をgrid [横, 縦] のコマに分けたもの（左上から右へ0, 1, 2, ...）
+// animation: sheetのコマを並べたもの
+//   loop: trueなら繰り返す falseなら最後のコマで止まり, nextがあればそのanimationへ移る
+//
+// Animatorは1countに1回updateする（描画のframeではなくゲームのcountで進む）
+
+fn default_duration() -> u32 {
+	1
+}
+
+fn default_scale() -> f32 {
+	1.0
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct AnimationData {
+	frames: Vec<u32>,
+	// sheetのコマの番号
+	#[serde(default = "default_duration")]
+	duration: u32,
+	// 1コマの表示count
+	#[serde(default)]
+	durations: Vec<u32>,
+	// コマ毎の表示count 空ならdurationを使う
+	#[serde(default, rename = "loop")]
+	looped: bool,
+	#[serde(default)]
+	next: Option<String>,
+	// 終わった後に再生するanimation
+	#[serde(default)]
+	spin: f32,
+	// 1countあたりの回転 (rad)
+	#[serde(default)]
+	fade: bool,
+	// 再生しながら透明にする
+}
+
+impl AnimationData {
+	fn duration(&self, i: usize) -> u32 {
+		self.durations.get(i).cloned().unwrap_or(self.duration).max(1)
+	}
+
+	fn length(&self) -> u32 {
+		(0..self.frames.len()).map(|i| self.duration(i)).sum()
+	}
+
+	// countで表示するコマ
+	fn frame(&self, count: u32) -> u32 {
+		let length = self.length();
+		let mut t = if self.looped { count % length } else { count.min(length - 1) };
+		for (i, f) in self.frames.iter().enumerate() {
+			let d = self.duration(i);
+			if t < d {
+				return *f;
+			}
+			t -= d;
+		}
+		*self.frames.last().unwrap()
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Sheet {
+	#[serde(default)]
+	pub image: Option<String>,
+	// resourcesからのpath 無い場合は四角で描画
+	#[serde(default = "default_grid")]
+	grid: [u32; 2],
+	// [横のコマ数, 縦のコマ数]
+	#[serde(default = "default_scale")]
+	pub scale: f32,
+	// 描画の倍率
+	animations: HashMap<String, AnimationData>,
+}
+
+fn default_grid() -> [u32; 2] {
+	[1, 1]
+}
+
+impl Sheet {
+	// コマの切り出し位置（0.0 ~ 1.0）
+	pub fn src(&self, frame: u32) -> graphics::Rect {
+		let w = 1.0 / self.grid[0] as f32;
+		let h = 1.0 / self.grid[1] as f32;
+		let x = (frame % self.grid[0]) as f32;
+		let y = (frame / self.grid[0]) as f32;
+		graphics::Rect::new(x * w, y * h, w, h)
+	}
+
+	pub fn has(&self, animation: &str) -> bool {
+		self.animations.contains_key(animation)
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct SpriteSheets {
+	sheets: HashMap<String, Sheet>,
+}
+
+impl SpriteSheets {
+	pub fn load(path: &str) -> GameResult<SpriteSheets> {
+		let v = load_json(path)?;
+		let sheets: HashMap<String, Sheet> = serde_json::from_value(v["sheet"].to_owned())
+			.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", path, e)))?;
+
+		// コマの番号, nextの名前が正しいか
+		for (name, sheet) in &sheets {
+			let frames = sheet.grid[0] * sheet.grid[1];
+			if frames == 0 {
+				return Err(GameError::ResourceLoadError(format!("{}: sheet \"{}\": grid is empty", path, name)));
+			}
+			for (an, a) in &sheet.animations {
+				if a.frames.is_empty() {
+					return Err(GameError::ResourceLoadError(
+						format!("{}: sheet \"{}\": animation \"{}\" has no frames", path, name, an)
+					));
+				}
+				if let Some(f) = a.frames.iter().find(|f| **f >= frames) {
+					return Err(GameError::ResourceLoadError(
+						format!("{}: sheet \"{}\": animation \"{}\": frame {} is out of the grid", path, name, an, f)
+					));
+				}
+				if let Some(ref next) = a.next {
+					if !sheet.animations.contains_key(next) {
+						return Err(GameError::ResourceLoadError(
+							format!("{}: sheet \"{}\": animation \"{}\" is not found", path, name, next)
+						));
+					}
+				}
+			}
+		}
+		Ok(SpriteSheets { sheets })
+	}
+
+	pub fn get(&self, name: &str) -> Option<&Sheet> {
+		self.sheets.get(name)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&String, &Sheet)> {
+		self.sheets.iter()
+	}
+
+	// sheetにanimationが全てあるか
+	pub fn require(&self, sheet: &str, animations: &[&str]) -> GameResult<()> {
+		let s = self.get(sheet)
+			.ok_or_else(|| GameError::ResourceLoadError(format!("sprites.json: sheet \"{}\" is not defined", sheet)))?;
+		match animations.iter().find(|a| !s.has(a)) {
+			Some(a) => Err(GameError::ResourceLoadError(
+				format!("sprites.json: sheet \"{}\": animation \"{}\" is not defined", sheet, a)
+			)),
+			None => Ok(()),
+		}
+	}
+}
+
+// 描画するコマ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+	pub frame: u32,
+	pub rotation: f32,
+	pub alpha: f32,
+	// 0.0 ~ 1.0
+}
+
+// Actor毎のアニメーションの再生位置
+// sheetが空なら何も再生しない
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Animator {
+	sheet: String,
+	animation: String,
+	count: u32,
+	// animationを始めてからのcount
+}
+
+impl Animator {
+	pub fn new(sheet: &str, animation: &str) -> Animator {
+		Animator {
+			sheet: sheet.to_owned(),
+			animation: animation.to_owned(),
+			count: 0,
+		}
+	}
+
+	pub fn sheet(&self) -> &str {
+		&self.sheet
+	}
+
+	pub fn animation(&self) -> &str {
+		&self.animation
+	}
+
+	// 同じanimationなら最初に戻さない
+	pub fn play(&mut self, animation: &str) {
+		if self.animation != animation {
+			self.animation = animation.to_owned();
+			self.count = 0;
+		}
+	}
+
+	fn data<'a>(&self, sheets: &'a SpriteSheets) -> Option<&'a AnimationData> {
+		sheets.get(&self.sheet).and_then(|s| s.animations.get(&self.animation))
+	}
+
+	pub fn update(&mut self, sheets: &SpriteSheets) {
+		self.count += 1;
+		let next = match self.data(sheets) {
+			Some(a) if !a.looped && self.count >= a.length() => a.next.clone(),
+			_ => None,
+		};
+		if let Some(next) = next {
+			self.play(&next);
+		}
+	}
+
+	// loopしないanimationを最後まで再生した
+	pub fn finished(&self, sheets: &SpriteSheets) -> bool {
+		match self.data(sheets) {
+			Some(a) => !a.looped && a.next.is_none() && self.count >= a.length(),
+			None => true,
+		}
+	}
+
+	pub fn frame(&self, sheets: &SpriteSheets) -> Option<Frame> {
+		self.data(sheets).map(|a| {
+			let alpha = if a.fade {
+				1.0 - (self.count as f32 / a.length() as f32).min(1.0)
+			} else {
+				1.0
+			};
+			Frame {
+				frame: a.frame(self.count),
+				rotation: a.spin * self.count as f32,
+				alpha,
+			}
+		})
+	}
+}
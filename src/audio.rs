@@ -0,0 +1,415 @@
+use ggez::audio::{SoundData, Source};
+use ggez::{Context, GameResult};
+use ggez::error::GameError;
+
+use serde_json;
+
+use std::collections::HashMap;
+
+use load_json;
+
+// BGMと効果音
+// audio.jsonで曲と効果音のファイルを決める
+//
+// BGMはintroを1回流してから, loopを繰り返す（introの再生が終わったらloopを始める）
+// 曲を変える時は前の曲をcrossfadeの秒数で小さくしながら次の曲を大きくする
+// 効果音は種類毎にintervalのcount数に1回だけ鳴らす（弾が多くても重ならない）
+//
+// play_bgm, play_seは予約だけして, updateでまとめて鳴らす
+// Backend::Nullでは音を出さずに予約, 間隔の処理だけ行う（音声デバイスの無い環境用）
+
+const MAX_VOICES: usize = 32;
+// 同時に鳴らす効果音の数
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Se {
+	Shot,
+	Hit,
+	EnemyDeath,
+	Graze,
+	Bomb,
+	PlayerDeath,
+	Item,
+	SpellDeclare,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BgmData {
+	#[serde(default)]
+	intro: Option<String>,
+	#[serde(rename = "loop")]
+	looped: String,
+}
+
+fn default_interval() -> u32 {
+	1
+}
+
+fn default_volume() -> f32 {
+	1.0
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SeData {
+	file: String,
+	#[serde(default = "default_interval")]
+	interval: u32,
+	// 同じ効果音を次に鳴らせるまでのcount
+	#[serde(default = "default_volume")]
+	volume: f32,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AudioData {
+	#[serde(default)]
+	crossfade: f32,
+	// 秒
+	#[serde(default)]
+	bgm: HashMap<String, BgmData>,
+	#[serde(default)]
+	se: HashMap<Se, SeData>,
+}
+
+pub fn load(path: &str) -> GameResult<AudioData> {
+	serde_json::from_value(load_json(path)?)
+		.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", path, e)))
+}
+
+#[derive(Debug)]
+enum Backend {
+	Null,
+	Device(HashMap<String, SoundData>),
+	// ファイル名 -> 読み込んだ音声 読めなかったファイルは入っていない
+}
+
+impl Backend {
+	fn source(&self, ctx: &mut Context, file: &str) -> Option<Source> {
+		match *self {
+			Backend::Null => None,
+			Backend::Device(ref sounds) => sounds.get(file).and_then(|d| {
+				Source::from_data(ctx, d.clone())
+					.map_err(|e| eprintln!("{}: {}", file, e))
+					.ok()
+			}),
+		}
+	}
+}
+
+// 再生中のBGM
+#[derive(Debug)]
+struct Track {
+	name: String,
+	started: bool,
+	// updateで再生を始めた
+	looping: bool,
+	// introが終わってloopに入った
+	fade: f32,
+	// 音量の倍率 0 ~ 1
+	fade_speed: f32,
+	// 1秒あたりのfadeの変化 負ならfade out
+	intro: Option<Source>,
+	looped: Option<Source>,
+}
+
+impl Track {
+	fn fading_out(&self) -> bool {
+		self.fade_speed < 0.0
+	}
+
+	fn set_volume(&mut self, volume: f32) {
+		for s in self.intro.iter_mut().chain(self.looped.iter_mut()) {
+			s.set_volume(volume * self.fade);
+		}
+	}
+
+	fn stop(&self) {
+		for s in self.intro.iter().chain(self.looped.iter()) {
+			s.stop();
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Audio {
+	data: AudioData,
+	backend: Backend,
+	tracks: Vec<Track>,
+	// 最後が今の曲 他はfade out中
+	pending_se: Vec<Se>,
+	last_se: HashMap<Se, u32>,
+	// 最後に鳴らしたcount
+	count: u32,
+	voices: Vec<Source>,
+	// 鳴っている効果音
+	bgm_volume: f32,
+	se_volume: f32,
+	paused: bool,
+}
+
+impl Audio {
+	// audio.jsonのファイルを全て読み込む
+	// 無いファイルは鳴らさない
+	pub fn new(ctx: &mut Context, data: AudioData) -> Audio {
+		let mut files: Vec<&String> = data.se.values().map(|s| &s.file).collect();
+		for b in data.bgm.values() {
+			files.push(&b.looped);
+			files.extend(b.intro.iter());
+		}
+		let mut sounds = HashMap::new();
+		let mut missing = 0;
+		for file in files {
+			match SoundData::new(ctx, file) {
+				Ok(d) => {
+					sounds.insert(file.clone(), d);
+				},
+				Err(_) => missing += 1,
+			}
+		}
+		if missing > 0 {
+			eprintln!("audio: {} files could not be loaded", missing);
+		}
+		Audio::with_backend(data, Backend::Device(sounds))
+	}
+
+	pub fn null(data: AudioData) -> Audio {
+		Audio::with_backend(data, Backend::Null)
+	}
+
+	fn with_backend(data: AudioData, backend: Backend) -> Audio {
+		Audio {
+			data,
+			backend,
+			tracks: Vec::new(),
+			pending_se: Vec::new(),
+			last_se: HashMap::new(),
+			count: 0,
+			voices: Vec::new(),
+			bgm_volume: 1.0,
+			se_volume: 1.0,
+			paused: false,
+		}
+	}
+
+	// 音声デバイスを使わないようにする
+	pub fn mute(&mut self) {
+		for t in &self.tracks {
+			t.stop();
+		}
+		self.tracks.clear();
+		self.voices.clear();
+		self.backend = Backend::Null;
+	}
+
+	// bgm, se: 0 ~ 100
+	pub fn set_volume(&mut self, bgm: u32, se: u32) {
+		self.bgm_volume = bgm as f32 / 100.0;
+		self.se_volume = se as f32 / 100.0;
+		let volume = self.bgm_volume;
+		for t in &mut self.tracks {
+			t.set_volume(volume);
+		}
+	}
+
+	pub fn bgm(&self) -> Option<&str> {
+		self.tracks.last()
+			.filter(|t| !t.fading_out())
+			.map(|t| t.name.as_str())
+	}
+
+	// 同じ曲が流れていれば何もしない
+	// audio.jsonに無い曲なら今の曲を止める
+	pub fn play_bgm(&mut self, name: &str) {
+		if self.bgm() == Some(name) {
+			return;
+		}
+		// まだ鳴らしていない曲は捨てる
+		self.tracks.retain(|t| t.started);
+		self.stop_bgm();
+		if !self.data.bgm.contains_key(name) {
+			return;
+		}
+		let crossfade = self.data.crossfade;
+		let fade_in = !self.tracks.is_empty() && crossfade > 0.0;
+		self.tracks.push(Track {
+			name: name.to_owned(),
+			started: false,
+			looping: false,
+			fade: if fade_in { 0.0 } else { 1.0 },
+			fade_speed: if fade_in { 1.0 / crossfade } else { 0.0 },
+			intro: None,
+			looped: None,
+		});
+	}
+
+	pub fn stop_bgm(&mut self) {
+		let crossfade = self.data.crossfade;
+		for t in &mut self.tracks {
+			t.fade_speed = if crossfade > 0.0 { -1.0 / crossfade } else { -1.0e6 };
+		}
+	}
+
+	// 前に鳴らしてからintervalが経っていなければ鳴らさない
+	pub fn play_se(&mut self, se: Se) {
+		let interval = match self.data.se.get(&se) {
+			Some(sd) => sd.interval,
+			None => return,
+		};
+		if let Some(last) = self.last_se.get(&se) {
+			if self.count < last + interval {
+				return;
+			}
+		}
+		self.last_se.insert(se, self.count);
+		self.pending_se.push(se);
+	}
+
+	// Pause中はBGMを止める
+	pub fn set_paused(&mut self, paused: bool) {
+		if self.paused == paused {
+			return;
+		}
+		self.paused = paused;
+		for t in &self.tracks {
+			for s in t.intro.iter().chain(t.looped.iter()) {
+				if paused {
+					s.pause();
+				} else {
+					s.resume();
+				}
+			}
+		}
+	}
+
+	// 1count毎に呼ぶ
+	pub fn update(&mut self, ctx: &mut Context, seconds: f32) {
+		self.count += 1;
+
+		// 効果音----------
+		self.voices.retain(|v| v.playing());
+		for se in self.pending_se.drain(..) {
+			if self.voices.len() >= MAX_VOICES {
+				break;
+			}
+			let sd = &self.data.se[&se];
+			if let Some(mut source) = self.backend.source(ctx, &sd.file) {
+				source.set_volume(self.se_volume * sd.volume);
+				match source.play() {
+					Ok(_) => self.voices.push(source),
+					Err(e) => eprintln!("{}: {}", sd.file, e),
+				}
+			}
+		}
+		// -------------------------
+
+		// BGM----------
+		if self.paused {
+			return;
+		}
+		let volume = self.bgm_volume;
+		let backend = &self.backend;
+		for t in &mut self.tracks {
+			let bd = &self.data.bgm[&t.name];
+			if !t.started {
+				t.started = true;
+				t.intro = bd.intro.as_ref().and_then(|f| backend.source(ctx, f));
+				t.looped = backend.source(ctx, &bd.looped);
+				if let Some(ref mut s) = t.looped {
+					s.set_repeat(true);
+				}
+				t.set_volume(volume);
+				t.looping = t.intro.is_none();
+				let first = if t.looping { &t.looped } else { &t.intro };
+				if let Some(ref s) = *first {
+					let _ = s.play();
+				}
+			}
+
+			// ゲームのcountではなくintroの再生状態で切り替える（処理落ちしてもずれない）
+			// 一時停止中のSourceはstoppedにならない
+			let intro_done = t.intro.as_ref().map_or(true, |s| s.stopped());
+			if !t.looping && intro_done {
+				t.looping = true;
+				if let Some(ref s) = t.looped {
+					let _ = s.play();
+				}
+			}
+			t.fade = (t.fade + t.fade_speed * seconds).max(0.0).min(1.0);
+			if t.fade_speed != 0.0 {
+				t.set_volume(volume);
+			}
+			if t.fade >= 1.0 && t.fade_speed > 0.0 {
+				t.fade_speed = 0.0;
+			}
+		}
+		self.tracks.retain(|t| {
+			let done = t.fading_out() && t.fade <= 0.0;
+			if done {
+				t.stop();
+			}
+			!done
+		});
+		// -------------------------
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::Path;
+
+	fn null_audio() -> Audio {
+		let data = serde_json::from_str(r#"{
+			"se": {
+				"shot": { "file": "/se/shot.wav", "interval": 6 },
+				"bomb": { "file": "/se/bomb.wav" }
+			}
+		}"#).unwrap();
+		Audio::null(data)
+	}
+
+	#[test]
+	fn play_se_waits_for_interval() {
+		let mut audio = null_audio();
+		audio.play_se(Se::Shot);
+		audio.play_se(Se::Shot);
+		assert_eq!(audio.pending_se, vec![Se::Shot]);
+		// updateの代わりにcountを進める
+		for _ in 0..5 {
+			audio.count += 1;
+			audio.play_se(Se::Shot);
+		}
+		assert_eq!(audio.pending_se, vec![Se::Shot]);
+		audio.count += 1;
+		audio.play_se(Se::Shot);
+		assert_eq!(audio.pending_se, vec![Se::Shot, Se::Shot]);
+	}
+
+	#[test]
+	fn play_se_counts_interval_per_sound() {
+		let mut audio = null_audio();
+		audio.play_se(Se::Shot);
+		audio.play_se(Se::Bomb);
+		// audio.jsonに無い効果音は鳴らさない
+		audio.play_se(Se::Graze);
+		assert_eq!(audio.pending_se, vec![Se::Shot, Se::Bomb]);
+		audio.count += 1;
+		audio.play_se(Se::Shot);
+		audio.play_se(Se::Bomb);
+		assert_eq!(audio.pending_se, vec![Se::Shot, Se::Bomb, Se::Bomb]);
+	}
+
+	#[test]
+	fn audio_json_files_exist() {
+		let data = load("resources/audio.json").unwrap();
+		let mut files: Vec<&String> = data.se.values().map(|s| &s.file).collect();
+		for b in data.bgm.values() {
+			files.push(&b.looped);
+			files.extend(b.intro.iter());
+		}
+		for file in files {
+			let path = format!("resources{}", file);
+			assert!(Path::new(&path).is_file(), "{} is missing", path);
+		}
+	}
+}
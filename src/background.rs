@@ -0,0 +1,316 @@
+use ggez::graphics;
+use ggez::{Context, GameResult};
+use ggez::error::GameError;
+
+use serde_json;
+
+use std::collections::HashMap;
+
+use {load_json, RELATIVE_X, RELATIVE_Y, STAGE_DOWN, STAGE_LEFT, STAGE_RIGHT, STAGE_UP};
+
+// ステージの背景
+// story.jsonの"background"にステージ毎に書く
+//   sets: 名前 -> 重ねる画像（下から順）
+//   events: ステージのcountで起こすイベント（scroll: スクロールの速さを変える, fade: 別のsetへ切り替える）
+//
+// 画像はステージの枠に並べて敷き詰め, 向きdirectionに動かす
+// 1秒の移動量 = speed + スクロールの速さ * parallax
+// countはステージのcount（Boss戦の間は止まる）でイベントを起こし, 画像はBoss戦の間も動き続ける
+
+fn default_direction() -> [f32; 2] {
+	[0.0, 1.0]
+}
+
+fn default_parallax() -> f32 {
+	1.0
+}
+
+fn default_tint() -> [u8; 4] {
+	[255, 255, 255, 255]
+}
+
+fn default_scale() -> f32 {
+	1.0
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LayerData {
+	image: String,
+	// resourcesからのpath
+	#[serde(default)]
+	speed: f32,
+	// 1秒の移動量 (px) スクロールの速さに関わらず動く分
+	#[serde(default = "default_direction")]
+	direction: [f32; 2],
+	// 動く向き 省略時は下
+	#[serde(default = "default_parallax")]
+	parallax: f32,
+	// スクロールの速さに掛ける 遠くの画像ほど小さくする
+	#[serde(default = "default_tint")]
+	tint: [u8; 4],
+	// [r, g, b, a]
+	#[serde(default = "default_scale")]
+	scale: f32,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventKind {
+	Scroll {
+		speed: f32,
+		// スクロールの速さ (px/s)
+		#[serde(default)]
+		frames: u32,
+		// 速さを変えるのに掛けるcount
+	},
+	Fade {
+		to: String,
+		// 切り替え先のset
+		#[serde(default)]
+		frames: u32,
+		// 切り替えに掛けるcount
+	},
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Event {
+	count: u32,
+	#[serde(flatten)]
+	kind: EventKind,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct BackgroundData {
+	sets: HashMap<String, Vec<LayerData>>,
+	#[serde(default)]
+	initial: String,
+	// 最初のset
+	#[serde(default)]
+	events: Vec<Event>,
+}
+
+// story.jsonからステージの背景を読み込む
+// 無ければ背景を描画しない
+pub fn load_background(stage_name: &str) -> GameResult<BackgroundData> {
+	let v = load_json("resources/story.json")?;
+	let bv = &v["background"][stage_name];
+	if bv.is_null() {
+		return Ok(BackgroundData::default());
+	}
+	let mut data: BackgroundData = serde_json::from_value(bv.to_owned())
+		.map_err(|e| GameError::ResourceLoadError(format!("story.json: background: {}", e)))?;
+
+	// 無いsetへの切り替えを弾く
+	let err = |name: &str| Err(GameError::ResourceLoadError(
+		format!("story.json: background: set \"{}\" is not found", name)
+	));
+	if !data.sets.contains_key(&data.initial) {
+		return err(&data.initial);
+	}
+	for ev in &data.events {
+		if let EventKind::Fade { ref to, .. } = ev.kind {
+			if !data.sets.contains_key(to) {
+				return err(to);
+			}
+		}
+	}
+	data.events.sort_by_key(|ev| ev.count);
+	Ok(data)
+}
+
+// 時間を掛けて値を変える
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Ramp {
+	from: f32,
+	to: f32,
+	count: u32,
+	frames: u32,
+}
+
+impl Ramp {
+	fn fixed(value: f32) -> Ramp {
+		Ramp { from: value, to: value, count: 0, frames: 0 }
+	}
+
+	fn start(&self, to: f32, frames: u32) -> Ramp {
+		Ramp { from: self.value(), to, count: 0, frames }
+	}
+
+	fn value(&self) -> f32 {
+		if self.count >= self.frames {
+			self.to
+		} else {
+			self.from + (self.to - self.from) * self.count as f32 / self.frames as f32
+		}
+	}
+
+	fn done(&self) -> bool {
+		self.count >= self.frames
+	}
+
+	fn update(&mut self) {
+		if !self.done() {
+			self.count += 1;
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Background {
+	data: BackgroundData,
+	images: HashMap<String, Option<graphics::Image>>,
+	// 読み込めなかった画像はNone
+	current: String,
+	previous: Option<String>,
+	// fade中の前のset
+	fade: Ramp,
+	// 0.0 ~ 1.0 currentの不透明度
+	scroll: Ramp,
+	offsets: HashMap<String, Vec<[f32; 2]>>,
+	// set毎, 画像毎の位置のずれ (px)
+	next_event: usize,
+}
+
+impl Background {
+	pub fn new(ctx: &mut Context, data: BackgroundData) -> Background {
+		let mut images = HashMap::new();
+		for layer in data.sets.values().flat_map(|s| s.iter()) {
+			if !images.contains_key(&layer.image) {
+				let image = graphics::Image::new(ctx, &layer.image)
+					.map_err(|e| eprintln!("{}: {}", layer.image, e))
+					.ok();
+				images.insert(layer.image.clone(), image);
+			}
+		}
+		let offsets = data.sets.iter()
+			.map(|(name, layers)| (name.clone(), vec![[0.0; 2]; layers.len()]))
+			.collect();
+		let mut bg = Background {
+			current: data.initial.clone(),
+			data,
+			images,
+			previous: None,
+			fade: Ramp::fixed(1.0),
+			scroll: Ramp::fixed(0.0),
+			offsets,
+			next_event: 0,
+		};
+		bg.reset(0);
+		bg
+	}
+
+	// countまでのイベントをすぐに反映する（Practiceで途中から始める時）
+	pub fn reset(&mut self, count: u32) {
+		self.current = self.data.initial.clone();
+		self.previous = None;
+		self.fade = Ramp::fixed(1.0);
+		self.scroll = Ramp::fixed(0.0);
+		for o in self.offsets.values_mut() {
+			for p in o.iter_mut() {
+				*p = [0.0; 2];
+			}
+		}
+		self.next_event = 0;
+		while self.next_event < self.data.events.len() && self.data.events[self.next_event].count <= count {
+			let kind = self.data.events[self.next_event].kind.clone();
+			match kind {
+				EventKind::Scroll { speed, .. } => self.scroll = Ramp::fixed(speed),
+				EventKind::Fade { to, .. } => self.current = to,
+			}
+			self.next_event += 1;
+		}
+	}
+
+	fn apply(&mut self, kind: EventKind) {
+		match kind {
+			EventKind::Scroll { speed, frames } => self.scroll = self.scroll.start(speed, frames),
+			EventKind::Fade { to, frames } => {
+				if to != self.current {
+					self.previous = Some(self.current.clone());
+					self.current = to;
+					self.fade = Ramp { from: 0.0, to: 1.0, count: 0, frames };
+				}
+			},
+		}
+	}
+
+	// 1count毎に呼ぶ count: ステージのcount
+	pub fn update(&mut self, count: u32, seconds: f32) {
+		while self.next_event < self.data.events.len() && self.data.events[self.next_event].count <= count {
+			let kind = self.data.events[self.next_event].kind.clone();
+			self.apply(kind);
+			self.next_event += 1;
+		}
+
+		let scroll = self.scroll.value();
+		let shown: Vec<String> = self.previous.iter().chain(Some(&self.current)).cloned().collect();
+		for name in shown {
+			// 背景の無いステージはsetも無い
+			let (layers, offsets) = match (self.data.sets.get(&name), self.offsets.get_mut(&name)) {
+				(Some(layers), Some(offsets)) => (layers, offsets),
+				_ => continue,
+			};
+			for (layer, o) in layers.iter().zip(offsets.iter_mut()) {
+				let v = (layer.speed + scroll * layer.parallax) * seconds;
+				o[0] += layer.direction[0] * v;
+				o[1] += layer.direction[1] * v;
+			}
+		}
+
+		self.scroll.update();
+		self.fade.update();
+		if self.fade.done() {
+			self.previous = None;
+		}
+	}
+
+	fn draw_set(&self, ctx: &mut Context, name: &str, alpha: f32) -> GameResult<()> {
+		let layers = match self.data.sets.get(name) {
+			Some(layers) => layers,
+			None => return Ok(()),
+		};
+		for (layer, o) in layers.iter().zip(self.offsets[name].iter()) {
+			let image = match self.images.get(&layer.image).and_then(|i| i.as_ref()) {
+				Some(image) => image,
+				None => continue,
+			};
+			let scale = [layer.scale * RELATIVE_X, layer.scale * RELATIVE_Y];
+			let w = image.width() as f32 * scale[0];
+			let h = image.height() as f32 * scale[1];
+			if w <= 0.0 || h <= 0.0 {
+				continue;
+			}
+			let t = layer.tint;
+			let color = graphics::Color::from((t[0], t[1], t[2], (t[3] as f32 * alpha) as u8));
+
+			// ステージの左上より左上の位置から敷き詰める
+			let x0 = STAGE_LEFT + (o[0] * RELATIVE_X).rem_euclid(w) - w;
+			let y0 = STAGE_UP + (o[1] * RELATIVE_Y).rem_euclid(h) - h;
+			let mut y = y0;
+			while y < STAGE_DOWN {
+				let mut x = x0;
+				while x < STAGE_RIGHT {
+					let params = graphics::DrawParam {
+						dest: graphics::Point2::new(x, y),
+						scale: graphics::Point2::new(scale[0], scale[1]),
+						color: Some(color),
+						..Default::default()
+					};
+					graphics::draw_ex(ctx, image, params)?;
+					x += w;
+				}
+				y += h;
+			}
+		}
+		Ok(())
+	}
+
+	// fade中は前のsetの上に今のsetを重ねる
+	pub fn draw(&self, ctx: &mut Context) -> GameResult<()> {
+		if let Some(ref previous) = self.previous {
+			self.draw_set(ctx, previous, 1.0)?;
+		}
+		self.draw_set(ctx, &self.current, self.fade.value())?;
+		graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))
+	}
+}
@@ -0,0 +1,50 @@
+extern crate ggez;
+extern crate poi_project;
+
+use ggez::event;
+use ggez::ContextBuilder;
+use ggez::conf;
+
+use std::{path, env};
+
+use poi_project::path_preview::PathPreview;
+use poi_project::SCREEN_WIDTH;
+use poi_project::SCREEN_HEIGHT;
+
+// story.jsonのEnemyの軌道を確認する
+// cargo run --bin path_preview [stage名] [ファイル]
+// pathの例: cargo run --bin path_preview path_example resources/path_example.json
+pub fn main() {
+	let stage_name = env::args().nth(1).unwrap_or("stage1".to_owned());
+	let file = env::args().nth(2).unwrap_or("resources/story.json".to_owned());
+
+	let w = SCREEN_WIDTH as u32;
+	let h = SCREEN_HEIGHT as u32;
+	let mut cb = ContextBuilder::new("poi-project-path-preview", "ggez")
+		.window_setup(conf::WindowSetup::default()
+					  .title("poi-project path preview")
+					  )
+		.window_mode(conf::WindowMode::default()
+					 .dimensions(w, h)
+					 );
+
+	if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+		let mut path = path::PathBuf::from(manifest_dir);
+		path.push("resources");
+		cb = cb.add_resource_path(path);
+	}
+
+	let ctx = &mut cb.build().unwrap();
+
+	match PathPreview::new(ctx, &file, &stage_name) {
+		Err(e) => {
+			println!("Could not load stage!");
+			println!("Error: {}", e);
+		}
+		Ok(ref mut preview) => {
+			if let Err(e) = event::run(ctx, preview) {
+				println!("Error encountered running preview: {}", e);
+			}
+		}
+	}
+}
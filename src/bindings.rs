@@ -0,0 +1,276 @@
+use ggez::event::{Axis, Button, Keycode};
+
+use std::collections::BTreeMap;
+
+use InputState;
+
+// キー設定
+// 1つの操作にキーボードのkeyとゲームパッドのbuttonを複数割り当てられる
+// 名前で保存する（Keycode::name(), Button::string(), Axis::string()）
+//
+// Controlsが押しているkey, buttonとスティックの傾きを覚えて,
+// Bindingsで操作に変換してInputStateを作る
+
+pub const MAX_KEYS: usize = 2;
+// 1つの操作に割り当てるkey, buttonの数 [通常, 代わり]
+const DEAD_ZONE: i16 = 8000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+	Up,
+	Down,
+	Left,
+	Right,
+	Shot,
+	// メニューの決定
+	Bomb,
+	// メニューの戻る
+	Slow,
+	Pause,
+	// メニューの戻る
+	Skip,
+	// 会話を飛ばす
+	FastForward,
+	// 押している間は会話を早送り
+}
+
+impl Action {
+	pub const ALL: [Action; 10] = [
+		Action::Up,
+		Action::Down,
+		Action::Left,
+		Action::Right,
+		Action::Shot,
+		Action::Bomb,
+		Action::Slow,
+		Action::Pause,
+		Action::Skip,
+		Action::FastForward,
+	];
+
+	// localeのkey
+	pub fn key(&self) -> &'static str {
+		match *self {
+			Action::Up => "action.up",
+			Action::Down => "action.down",
+			Action::Left => "action.left",
+			Action::Right => "action.right",
+			Action::Shot => "action.shot",
+			Action::Bomb => "action.bomb",
+			Action::Slow => "action.slow",
+			Action::Pause => "action.pause",
+			Action::Skip => "action.skip",
+			Action::FastForward => "action.fast_forward",
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Bindings {
+	pub keys: BTreeMap<Action, Vec<String>>,
+	// Keycode::name()
+	pub buttons: BTreeMap<Action, Vec<String>>,
+	// Button::string()
+	pub axes: [String; 2],
+	// 移動に使うスティック [横, 縦] Axis::string()
+	pub dead_zone: i16,
+	// これより小さい傾きは無視する
+}
+
+impl Default for Bindings {
+	fn default() -> Bindings {
+		let table = |list: &[(Action, &[&str])]| -> BTreeMap<Action, Vec<String>> {
+			list.iter()
+				.map(|&(a, names)| (a, names.iter().map(|s| s.to_string()).collect()))
+				.collect()
+		};
+		Bindings {
+			keys: table(&[
+				(Action::Up, &["Up", "W"]),
+				(Action::Down, &["Down", "S"]),
+				(Action::Left, &["Left", "A"]),
+				(Action::Right, &["Right", "D"]),
+				(Action::Shot, &["Z", "Return"]),
+				(Action::Bomb, &["X"]),
+				(Action::Slow, &["Left Shift", "Right Shift"]),
+				(Action::Pause, &["Escape", "P"]),
+				(Action::Skip, &["C"]),
+				(Action::FastForward, &["Left Ctrl", "Right Ctrl"]),
+			]),
+			buttons: table(&[
+				(Action::Up, &["dpup"]),
+				(Action::Down, &["dpdown"]),
+				(Action::Left, &["dpleft"]),
+				(Action::Right, &["dpright"]),
+				(Action::Shot, &["a"]),
+				(Action::Bomb, &["b"]),
+				(Action::Slow, &["rightshoulder", "x"]),
+				(Action::Pause, &["start"]),
+				(Action::Skip, &["back"]),
+				(Action::FastForward, &["y"]),
+			]),
+			axes: ["leftx".to_owned(), "lefty".to_owned()],
+			dead_zone: DEAD_ZONE,
+		}
+	}
+}
+
+// nameが割り当てられている操作
+fn find(table: &BTreeMap<Action, Vec<String>>, name: &str) -> Option<Action> {
+	table.iter()
+		.find(|&(_, names)| names.iter().any(|n| n == name))
+		.map(|(a, _)| *a)
+}
+
+// nameを他の操作から外して, actionの先頭に入れる
+fn bind(table: &mut BTreeMap<Action, Vec<String>>, action: Action, name: String) {
+	for names in table.values_mut() {
+		names.retain(|n| *n != name);
+	}
+	let names = table.entry(action).or_default();
+	names.insert(0, name);
+	names.truncate(MAX_KEYS);
+}
+
+// tableに無い操作にdefaultのnameを入れる
+fn fill(table: &mut BTreeMap<Action, Vec<String>>, default: &BTreeMap<Action, Vec<String>>) {
+	for (action, names) in default {
+		if table.contains_key(action) {
+			continue;
+		}
+		let free = names.iter()
+			.filter(|n| find(table, n).is_none())
+			.cloned()
+			.collect();
+		table.insert(*action, free);
+	}
+}
+
+impl Bindings {
+	// 設定ファイルに無い操作（後から追加した操作）に初期値を割り当てる
+	// 初期値のkeyが他の操作に使われていれば割り当てない
+	pub fn fill_defaults(&mut self) {
+		let defaults = Bindings::default();
+		fill(&mut self.keys, &defaults.keys);
+		fill(&mut self.buttons, &defaults.buttons);
+	}
+
+	pub fn key_action(&self, keycode: Keycode) -> Option<Action> {
+		find(&self.keys, &keycode.name())
+	}
+
+	pub fn button_action(&self, button: Button) -> Option<Action> {
+		find(&self.buttons, &button.string())
+	}
+
+	pub fn bind_key(&mut self, action: Action, keycode: Keycode) {
+		bind(&mut self.keys, action, keycode.name());
+	}
+
+	pub fn bind_button(&mut self, action: Action, button: Button) {
+		bind(&mut self.buttons, action, button.string());
+	}
+
+	// Key configの表示 "Z / Return  [a]"
+	pub fn label(&self, action: Action) -> String {
+		let keys = self.keys.get(&action).map_or(String::new(), |k| k.join(" / "));
+		let buttons = self.buttons.get(&action).map_or(String::new(), |b| b.join(" / "));
+		if buttons.is_empty() {
+			keys
+		} else {
+			format!("{}  [{}]", keys, buttons)
+		}
+	}
+}
+
+// 今押しているkey, buttonとスティックの傾き
+#[derive(Debug, Clone)]
+pub struct Controls {
+	pub bindings: Bindings,
+	keys: Vec<Keycode>,
+	buttons: Vec<Button>,
+	axis: [i16; 2],
+	// [横, 縦] 上, 左が負
+}
+
+impl Controls {
+	pub fn new(bindings: Bindings) -> Controls {
+		Controls {
+			bindings,
+			keys: Vec::new(),
+			buttons: Vec::new(),
+			axis: [0, 0],
+		}
+	}
+
+	// Windowのフォーカスが外れた時など
+	pub fn clear(&mut self) {
+		self.keys.clear();
+		self.buttons.clear();
+		self.axis = [0, 0];
+	}
+
+	// 押したkeyの操作を返す（メニューに渡す）
+	pub fn key_down(&mut self, keycode: Keycode) -> Option<Action> {
+		if !self.keys.contains(&keycode) {
+			self.keys.push(keycode);
+		}
+		self.bindings.key_action(keycode)
+	}
+
+	pub fn key_up(&mut self, keycode: Keycode) -> Option<Action> {
+		self.keys.retain(|k| *k != keycode);
+		self.bindings.key_action(keycode)
+	}
+
+	pub fn button_down(&mut self, button: Button) -> Option<Action> {
+		if !self.buttons.contains(&button) {
+			self.buttons.push(button);
+		}
+		self.bindings.button_action(button)
+	}
+
+	pub fn button_up(&mut self, button: Button) -> Option<Action> {
+		self.buttons.retain(|b| *b != button);
+		self.bindings.button_action(button)
+	}
+
+	// スティックが中央から倒れた時だけ方向の操作を返す
+	pub fn axis(&mut self, axis: Axis, value: i16) -> Option<Action> {
+		let name = axis.string();
+		let i = self.bindings.axes.iter().position(|a| *a == name)?;
+		let dz = self.bindings.dead_zone;
+		let was_neutral = -dz <= self.axis[i] && self.axis[i] <= dz;
+		self.axis[i] = value;
+		if !was_neutral {
+			return None;
+		}
+		match (i, value) {
+			(0, v) if v < -dz => Some(Action::Left),
+			(0, v) if v > dz => Some(Action::Right),
+			(1, v) if v < -dz => Some(Action::Up),
+			(1, v) if v > dz => Some(Action::Down),
+			_ => None,
+		}
+	}
+
+	pub fn is_held(&self, action: Action) -> bool {
+		self.keys.iter().any(|k| self.bindings.key_action(*k) == Some(action))
+			|| self.buttons.iter().any(|b| self.bindings.button_action(*b) == Some(action))
+	}
+
+	pub fn state(&self) -> InputState {
+		let dz = self.bindings.dead_zone;
+		InputState {
+			up: self.is_held(Action::Up) || self.axis[1] < -dz,
+			down: self.is_held(Action::Down) || self.axis[1] > dz,
+			right: self.is_held(Action::Right) || self.axis[0] > dz,
+			left: self.is_held(Action::Left) || self.axis[0] < -dz,
+			shift: self.is_held(Action::Slow),
+			shot: self.is_held(Action::Shot),
+			bomb: self.is_held(Action::Bomb),
+		}
+	}
+}
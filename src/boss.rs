@@ -0,0 +1,280 @@
+use ggez::GameResult;
+use ggez::error::GameError;
+
+use serde_json;
+use rand::Rng;
+
+use std::collections::HashMap;
+
+use path;
+use path::{Easing, PathElement, PathKind};
+use shot_type;
+use shot_type::Pattern;
+use {load_json, Actor, GameRng, MovingElement, RELATIVE_X, RELATIVE_Y};
+
+// boss.json の読み込みとBossの移動
+//
+// 登場: story.jsonの出現位置から home まで移動
+// 各phase:
+//   return_center: phaseの始めに home へ戻ってから弾幕を始める
+//   movement: 弾幕中の動き
+// 退場: 最後のphaseが終わると exit.to へ移動して消える
+//
+// 登場, 中央へ戻る, 退場の間は弾幕を撃たず, 当たり判定もない
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Motion {
+	pub to: [f32; 2],
+	// 移動先 [x, y]
+	pub duration: u32,
+	// 移動にかけるcount
+	#[serde(default)]
+	pub easing: Easing,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Movement {
+	None,
+	// 動かない
+	Drift {
+		interval: u32,
+		// 次の移動までのcount
+		duration: u32,
+		// 移動にかけるcount
+		area: [f32; 4],
+		// 移動範囲 [x1, y1, x2, y2]
+		#[serde(default)]
+		easing: Easing,
+	},
+	// area内のランダムな位置に移動
+	TrackPlayer {
+		interval: u32,
+		duration: u32,
+		max_step: f32,
+		// 1回の移動距離の上限
+		area: [f32; 2],
+		// x方向の移動範囲 [x1, x2]
+		#[serde(default)]
+		easing: Easing,
+	},
+	// Playerのxへ移動
+}
+
+impl Default for Movement {
+	fn default() -> Movement {
+		Movement::None
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct BossPhase {
+	#[serde(deserialize_with = "shot_type::deserialize_optional")]
+	pub shot_type: Option<Pattern>,
+	// 弾幕 noneなら撃たない（登場後の待ち時間）
+	pub time: u32,
+	// 制限時間のcount
+	pub life: f32,
+	// phaseの体力
+	#[serde(default)]
+	pub return_center: bool,
+	// phaseの始めにhomeへ戻る
+	#[serde(default)]
+	pub movement: Movement,
+	// 弾幕中の動き
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct BossData {
+	pub bbox_size: f32,
+	pub home: [f32; 2],
+	// 中央の位置
+	pub entrance_duration: u32,
+	// 登場してhomeへ移動するのにかけるcount
+	#[serde(default)]
+	pub entrance_easing: Easing,
+	pub center_duration: u32,
+	// return_centerで戻るのにかけるcount
+	#[serde(default)]
+	pub center_easing: Easing,
+	pub exit: Motion,
+	// 退場
+	pub phases: Vec<BossPhase>,
+}
+
+impl BossData {
+	// phaseをMovingElementの形に変換（count: 制限時間, shot_type: 弾幕）
+	pub fn moving(&self) -> Vec<MovingElement> {
+		self.phases.iter()
+			.map(|ph| MovingElement::new(ph.time, [0.0; 2], ph.shot_type))
+			.collect()
+	}
+
+	// Spell practiceで選べるphase [(phasesの番号, 弾幕)]
+	pub fn spells(&self) -> Vec<(usize, Pattern)> {
+		self.phases.iter().enumerate()
+			.filter_map(|(i, ph)| ph.shot_type.map(|p| (i, p)))
+			.collect()
+	}
+}
+
+pub fn load(path: &str) -> GameResult<HashMap<String, BossData>> {
+	let v = load_json(path)?;
+	let bosses: HashMap<String, BossData> = serde_json::from_value(v["boss"].to_owned())
+		.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", path, e)))?;
+
+	for (name, bd) in &bosses {
+		if bd.phases.is_empty() {
+			return Err(GameError::ResourceLoadError(format!("boss \"{}\": phases is empty", name)));
+		}
+	}
+	Ok(bosses)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BossStep {
+	Entrance,
+	Return,
+	Attack,
+	Exit,
+	Finished,
+}
+
+#[derive(Debug, Clone)]
+pub struct BossState {
+	data: BossData,
+	step: BossStep,
+	phase: usize,
+	motion_count: u32,
+	// 移動用のcount（phaseのcountとは別）
+}
+
+fn to_window(p: [f32; 2]) -> [f32; 2] {
+	[p[0] * RELATIVE_X, p[1] * RELATIVE_Y]
+}
+
+// countから移動を始める
+fn move_to(boss: &mut Actor, count: u32, to: [f32; 2], duration: u32, easing: Easing) {
+	boss.path = vec![PathElement {
+		count: count,
+		duration: duration.max(1),
+		kind: PathKind::MoveTo,
+		points: vec![to_window(to)],
+		easing: easing,
+		relative: false,
+	}];
+}
+
+impl BossState {
+	pub fn new(data: BossData, boss: &mut Actor) -> BossState {
+		move_to(boss, 0, data.home, data.entrance_duration, data.entrance_easing);
+		BossState {
+			data: data,
+			step: BossStep::Entrance,
+			phase: 0,
+			motion_count: 0,
+		}
+	}
+
+	pub fn step(&self) -> BossStep {
+		self.step
+	}
+
+	pub fn motion_count(&self) -> u32 {
+		self.motion_count
+	}
+
+	// 弾が当たるか
+	pub fn vulnerable(&self) -> bool {
+		self.step == BossStep::Attack
+	}
+
+	pub fn finished(&self) -> bool {
+		self.step == BossStep::Finished
+	}
+
+	// 現在のphaseの体力
+	pub fn phase_life(&self) -> f32 {
+		self.data.phases[self.phase].life
+	}
+
+	// 1count分Bossを動かす
+	// 弾幕を撃つ状態ならtrue
+	pub fn update(&mut self, boss: &mut Actor, p_point: [f32; 2], rand: &mut GameRng, dt: f32) -> bool {
+		let count = self.motion_count;
+		self.motion_count += 1;
+		let moving = path::update(boss, count, dt);
+
+		match self.step {
+			BossStep::Entrance | BossStep::Return => {
+				if !moving {
+					self.start_attack(boss);
+				}
+				false
+			},
+			BossStep::Attack => {
+				if !moving {
+					self.update_movement(boss, count, p_point, rand);
+				}
+				true
+			},
+			BossStep::Exit => {
+				if !moving {
+					self.step = BossStep::Finished;
+				}
+				false
+			},
+			BossStep::Finished => false,
+		}
+	}
+
+	fn start_attack(&mut self, boss: &mut Actor) {
+		self.step = BossStep::Attack;
+		self.motion_count = 0;
+		boss.path = Vec::new();
+	}
+
+	fn update_movement(&mut self, boss: &mut Actor, count: u32, p_point: [f32; 2], rand: &mut GameRng) {
+		match self.data.phases[self.phase].movement {
+			Movement::None => (),
+			Movement::Drift { interval, duration, area, easing } => {
+				if count % interval.max(1) == 0 {
+					let x = area[0] + (area[2] - area[0]) * rand.gen::<f32>();
+					let y = area[1] + (area[3] - area[1]) * rand.gen::<f32>();
+					move_to(boss, count + 1, [x, y], duration, easing);
+				}
+			},
+			Movement::TrackPlayer { interval, duration, max_step, area, easing } => {
+				if count % interval.max(1) == 0 {
+					let now_x = boss.point[0] / RELATIVE_X;
+					let p_x = p_point[0] / RELATIVE_X;
+					let dx = (p_x - now_x).max(-max_step).min(max_step);
+					let x = (now_x + dx).max(area[0]).min(area[1]);
+					let y = boss.point[1] / RELATIVE_Y;
+					move_to(boss, count + 1, [x, y], duration, easing);
+				}
+			},
+		}
+	}
+
+	// 次のphaseへ
+	// 最後のphaseなら退場を始めてfalse
+	pub fn next_phase(&mut self, boss: &mut Actor) -> bool {
+		self.motion_count = 0;
+		if self.phase + 1 >= self.data.phases.len() {
+			self.step = BossStep::Exit;
+			let exit = self.data.exit.clone();
+			move_to(boss, 0, exit.to, exit.duration, exit.easing);
+			return false;
+		}
+		self.phase += 1;
+		if self.data.phases[self.phase].return_center {
+			self.step = BossStep::Return;
+			let home = self.data.home;
+			move_to(boss, 0, home, self.data.center_duration, self.data.center_easing);
+		} else {
+			self.start_attack(boss);
+		}
+		true
+	}
+}
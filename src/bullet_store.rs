@@ -0,0 +1,227 @@
+use std::rc::Rc;
+
+use bullet_type::BulletStyle;
+use shot_behavior;
+use shot_behavior::Behavior;
+use shot_spawn;
+use shot_spawn::Spawn;
+use update_point_polar;
+
+// 敵弾の入れ物
+// 消えた弾のslotを空きリストに入れて使い回す
+// 弾の数が今までの最大を超えない限りメモリを確保しない
+//
+// 1countの流れ:
+//   update（移動, 子弾の生成） -> 当たり判定 -> sweep（空きslotの回収） -> append（子弾の追加）
+
+#[derive(Debug, Clone)]
+pub struct Bullet {
+	pub point: [f32; 2],
+	// 位置 [x, y]
+	pub prev_point: [f32; 2],
+	// 前のcountの位置（補間描画用）
+	pub velocity: [f32; 2],
+	// [角度, スカラ値]
+	pub accel: [f32; 2],
+	// [角度, スカラ値] それぞれ加速
+	pub count: u32,
+	pub style: BulletStyle,
+	// 弾の種類と色
+	pub behavior: Behavior,
+	// 撃った後の動き
+	pub spawns: Option<Rc<Vec<Spawn>>>,
+	// 子弾の生成 同じ弾幕の弾で共有する
+	pub grazed: bool,
+	// Grazeは1つの弾につき1回だけ
+	pub alive: bool,
+}
+
+impl Bullet {
+	pub fn new(point: [f32; 2], velocity: [f32; 2], style: BulletStyle) -> Bullet {
+		Bullet {
+			point,
+			velocity,
+			style,
+			..Default::default()
+		}
+	}
+}
+
+impl Default for Bullet {
+	fn default() -> Bullet {
+		Bullet {
+			point: [0.0; 2],
+			prev_point: [0.0; 2],
+			velocity: [0.0; 2],
+			accel: [0.0; 2],
+			count: 0,
+			style: BulletStyle::default(),
+			behavior: Behavior::None,
+			spawns: None,
+			grazed: false,
+			alive: true,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct BulletStore {
+	slots: Vec<Bullet>,
+	free: Vec<usize>,
+	// 空いているslotの番号 popで番号の小さい順に使う
+	speed_scale: f32,
+	// 追加する弾の速さの倍率（難易度）
+}
+
+impl Default for BulletStore {
+	fn default() -> BulletStore {
+		BulletStore::with_capacity(0)
+	}
+}
+
+impl BulletStore {
+	pub fn with_capacity(capacity: usize) -> BulletStore {
+		BulletStore {
+			slots: Vec::with_capacity(capacity),
+			free: Vec::with_capacity(capacity),
+			speed_scale: 1.0,
+		}
+	}
+
+	pub fn set_speed_scale(&mut self, scale: f32) {
+		self.speed_scale = scale;
+	}
+
+	// 生きている弾の数（sweep後に正確になる）
+	pub fn len(&self) -> usize {
+		self.slots.len() - self.free.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	pub fn push(&mut self, mut bullet: Bullet) {
+		bullet.prev_point = bullet.point;
+		bullet.velocity[1] *= self.speed_scale;
+		match self.free.pop() {
+			Some(i) => self.slots[i] = bullet,
+			None => self.slots.push(bullet),
+		}
+	}
+
+	// bulletsを空にして全て追加
+	pub fn append(&mut self, bullets: &mut Vec<Bullet>) {
+		for b in bullets.drain(..) {
+			self.push(b);
+		}
+	}
+
+	// 全て消す（確保したメモリは残す）
+	pub fn clear(&mut self) {
+		self.slots.clear();
+		self.free.clear();
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &Bullet> {
+		self.slots.iter().filter(|b| b.alive)
+	}
+
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Bullet> {
+		self.slots.iter_mut().filter(|b| b.alive)
+	}
+
+	// 1count分動かし, 生成された子弾をchildrenに入れる
+	// parents: Orbitで回る相手 [Actor.id, 位置]
+	pub fn update(&mut self, p_point: [f32; 2], parents: &[(u32, [f32; 2])], children: &mut Vec<Bullet>, dt: f32) {
+		for b in self.slots.iter_mut().filter(|b| b.alive) {
+			b.count += 1;
+			b.prev_point = b.point;
+			if !shot_behavior::update(b, p_point, parents, dt) {
+				b.alive = update_point_polar(&mut b.point, &mut b.velocity, b.accel, dt);
+			}
+			if b.alive {
+				shot_spawn::update(b, children);
+			} else {
+				shot_spawn::on_death(b, children);
+			}
+		}
+	}
+
+	// 消えた弾のslotを空きリストに入れる
+	pub fn sweep(&mut self) {
+		self.free.clear();
+		for (i, b) in self.slots.iter().enumerate().rev() {
+			if !b.alive {
+				self.free.push(i);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use STAGE_DOWN;
+
+	fn bullet(x: f32) -> Bullet {
+		Bullet::new([x, 300.0], [0.0, 0.0], BulletStyle::default())
+	}
+
+	fn xs(store: &BulletStore) -> Vec<f32> {
+		store.iter().map(|b| b.point[0]).collect()
+	}
+
+	#[test]
+	fn sweep_collects_dead_slots() {
+		let mut store = BulletStore::with_capacity(4);
+		for i in 0..4 {
+			store.push(bullet(100.0 + 10.0 * i as f32));
+		}
+		for b in store.iter_mut().filter(|b| b.point[0] == 110.0 || b.point[0] == 130.0) {
+			b.alive = false;
+		}
+		store.sweep();
+		assert_eq!(store.len(), 2);
+		assert_eq!(xs(&store), vec![100.0, 120.0]);
+	}
+
+	#[test]
+	fn push_reuses_free_slots_from_the_front() {
+		let mut store = BulletStore::with_capacity(4);
+		for i in 0..4 {
+			store.push(bullet(100.0 + 10.0 * i as f32));
+		}
+		for b in store.iter_mut().filter(|b| b.point[0] != 120.0) {
+			b.alive = false;
+		}
+		store.sweep();
+		store.push(bullet(200.0));
+		store.push(bullet(210.0));
+		// 空きslotを使うので増えない
+		assert_eq!(store.slots.len(), 4);
+		assert_eq!(xs(&store), vec![200.0, 210.0, 120.0]);
+		store.push(bullet(220.0));
+		store.push(bullet(230.0));
+		assert_eq!(store.slots.len(), 5);
+		assert_eq!(store.len(), 5);
+	}
+
+	#[test]
+	fn update_removes_bullets_leaving_the_stage() {
+		let mut store = BulletStore::default();
+		store.push(bullet(300.0));
+		// 真下へ画面外に出て行く
+		store.push(Bullet::new([300.0, STAGE_DOWN + 100.0], [0.0, 100.0], BulletStyle::default()));
+		let mut children = Vec::new();
+		store.update([0.0; 2], &[], &mut children, 1.0 / 60.0);
+		store.sweep();
+		assert_eq!(store.len(), 1);
+		assert_eq!(xs(&store), vec![300.0]);
+		assert!(children.is_empty());
+
+		store.clear();
+		assert!(store.is_empty());
+		assert!(store.free.is_empty());
+	}
+}
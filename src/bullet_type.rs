@@ -0,0 +1,278 @@
+use ggez::graphics;
+use ggez::GameResult;
+use ggez::error::GameError;
+
+use serde_json;
+
+use std::collections::BTreeMap;
+use std::f32::consts::PI;
+
+use sprite::Blend;
+use load_json;
+
+// bullet_type.json の読み込み
+// 敵弾の 画像, 当たり判定, 回転 を種類毎に持つ
+//
+// 弾の向き: Shotのvelocity[0]と同じ（0.0が真下, 1.0で半周）
+// 当たり判定は進行方向に合わせて回転する
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BulletKind {
+	Pellet,
+	// 小玉
+	Rice,
+	// 米粒
+	Knife,
+	// ナイフ
+	Star,
+	// 星
+	Orb,
+	// 大玉
+	Bubble,
+	// 泡
+}
+
+impl BulletKind {
+	pub const ALL: [BulletKind; 6] = [
+		BulletKind::Pellet,
+		BulletKind::Rice,
+		BulletKind::Knife,
+		BulletKind::Star,
+		BulletKind::Orb,
+		BulletKind::Bubble,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			BulletKind::Pellet => "pellet",
+			BulletKind::Rice => "rice",
+			BulletKind::Knife => "knife",
+			BulletKind::Star => "star",
+			BulletKind::Orb => "orb",
+			BulletKind::Bubble => "bubble",
+		}
+	}
+
+	fn index(self) -> usize {
+		self as usize
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BulletColor {
+	Red,
+	Orange,
+	Yellow,
+	Green,
+	Cyan,
+	Blue,
+	Purple,
+	White,
+}
+
+impl BulletColor {
+	pub const ALL: [BulletColor; 8] = [
+		BulletColor::Red,
+		BulletColor::Orange,
+		BulletColor::Yellow,
+		BulletColor::Green,
+		BulletColor::Cyan,
+		BulletColor::Blue,
+		BulletColor::Purple,
+		BulletColor::White,
+	];
+
+	// 画像で左から何番目か
+	fn index(self) -> usize {
+		self as usize
+	}
+
+	// レーザーなど画像を使わない描画の色
+	pub fn rgb(self) -> (u8, u8, u8) {
+		match self {
+			BulletColor::Red => (240, 50, 50),
+			BulletColor::Orange => (250, 140, 30),
+			BulletColor::Yellow => (240, 220, 40),
+			BulletColor::Green => (60, 210, 70),
+			BulletColor::Cyan => (40, 210, 220),
+			BulletColor::Blue => (50, 90, 240),
+			BulletColor::Purple => (170, 60, 230),
+			BulletColor::White => (230, 230, 230),
+		}
+	}
+}
+
+// 敵弾の見た目
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BulletStyle {
+	pub kind: BulletKind,
+	pub color: BulletColor,
+}
+
+impl BulletStyle {
+	pub fn new(kind: BulletKind, color: BulletColor) -> BulletStyle {
+		BulletStyle {
+			kind,
+			color,
+		}
+	}
+}
+
+impl Default for BulletStyle {
+	fn default() -> BulletStyle {
+		BulletStyle::new(BulletKind::Pellet, BulletColor::White)
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Hitbox {
+	Circle {
+		radius: f32,
+	},
+	Ellipse {
+		radius: [f32; 2],
+		// [横, 進行方向]
+	},
+	Capsule {
+		radius: f32,
+		length: f32,
+		// 進行方向の長さ（両端の半円は含まない）
+	},
+}
+
+impl Hitbox {
+	// 当たり判定を囲む円の半径
+	pub fn bound(&self) -> f32 {
+		match *self {
+			Hitbox::Circle { radius } => radius,
+			Hitbox::Ellipse { radius } => radius[0].max(radius[1]),
+			Hitbox::Capsule { radius, length } => radius + length / 2.0,
+		}
+	}
+
+	// along: 進行方向の距離, across: 横方向の距離, r: 相手の半径
+	fn hit_local(&self, along: f32, across: f32, r: f32) -> bool {
+		match *self {
+			Hitbox::Circle { radius } => {
+				let rr = radius + r;
+				along * along + across * across < rr * rr
+			},
+			Hitbox::Ellipse { radius } => {
+				// 相手の半径だけ膨らませた楕円で近似
+				let a = across / (radius[0] + r);
+				let b = along / (radius[1] + r);
+				a * a + b * b < 1.0
+			},
+			Hitbox::Capsule { radius, length } => {
+				// 線分との距離
+				let half = length / 2.0;
+				let nearest = along.max(-half).min(half);
+				let d = along - nearest;
+				let rr = radius + r;
+				d * d + across * across < rr * rr
+			},
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct BulletType {
+	pub sprite: [f32; 4],
+	// 1色目の位置 [x, y, w, h] (px)
+	pub hitbox: Hitbox,
+	pub rotate: bool,
+	// 進行方向に合わせて画像を回転する
+	pub spin: f32,
+	// 1countあたりの画像の回転 (rad)
+	#[serde(default)]
+	pub blend: Blend,
+	// addなら加算で描画する（光る弾）
+}
+
+#[derive(Debug, Clone)]
+pub struct BulletTypes {
+	pub image: String,
+	types: Vec<BulletType>,
+	// BulletKindの順
+}
+
+impl BulletTypes {
+	pub fn load(path: &str) -> GameResult<BulletTypes> {
+		let v = load_json(path)?;
+		let mut map: BTreeMap<String, BulletType> = serde_json::from_value(v["bullet_type"].to_owned())
+			.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", path, e)))?;
+		let image = v["image"].as_str()
+			.ok_or(GameError::ResourceLoadError(format!("{}: image is not found", path)))?
+			.to_owned();
+		let colors = v["colors"].as_u64().unwrap_or(1) as usize;
+		if colors < BulletColor::ALL.len() {
+			return Err(GameError::ResourceLoadError(
+				format!("{}: colors must be {} or more", path, BulletColor::ALL.len())
+			));
+		}
+
+		// BulletKindの全てが揃っているか, 余計なものが無いか
+		let mut types = Vec::new();
+		for kind in BulletKind::ALL.iter() {
+			match map.remove(kind.name()) {
+				Some(bt) => types.push(bt),
+				None => return Err(GameError::ResourceLoadError(
+					format!("{}: bullet_type \"{}\" is not found", path, kind.name())
+				)),
+			}
+		}
+		if let Some(name) = map.keys().next() {
+			return Err(GameError::ResourceLoadError(
+				format!("{}: unknown bullet_type \"{}\"", path, name)
+			));
+		}
+
+		Ok(BulletTypes {
+			image,
+			types,
+		})
+	}
+
+	pub fn get(&self, kind: BulletKind) -> &BulletType {
+		&self.types[kind.index()]
+	}
+
+	// 画像の切り出し位置（0.0 ~ 1.0）
+	pub fn src_rect(&self, style: BulletStyle, image_w: f32, image_h: f32) -> graphics::Rect {
+		let sp = self.get(style.kind).sprite;
+		let x = sp[0] + sp[2] * style.color.index() as f32;
+		graphics::Rect::new(x / image_w, sp[1] / image_h, sp[2] / image_w, sp[3] / image_h)
+	}
+
+	// 描画時の回転 (rad)
+	pub fn rotation(&self, style: BulletStyle, angle: f32, count: u32) -> f32 {
+		let bt = self.get(style.kind);
+		if bt.rotate {
+			// 画像は下向き
+			-angle * PI
+		} else {
+			bt.spin * count as f32
+		}
+	}
+
+	// 弾(point, 向きangle)と円(target, target_r)の当たり判定
+	pub fn hit(&self, style: BulletStyle, point: [f32; 2], angle: f32, target: [f32; 2], target_r: f32) -> bool {
+		let hitbox = self.get(style.kind).hitbox;
+		let dx = target[0] - point[0];
+		let dy = target[1] - point[1];
+
+		// 先に外接円で判定
+		let bound = hitbox.bound() + target_r;
+		if dx * dx + dy * dy >= bound * bound {
+			return false;
+		}
+
+		// 弾の向きの座標に直す
+		let rad = angle * PI;
+		let (sin, cos) = rad.sin_cos();
+		let along = dx * sin + dy * cos;
+		let across = dx * cos - dy * sin;
+		hitbox.hit_local(along, across, target_r)
+	}
+}
@@ -0,0 +1,230 @@
+use ggez::graphics;
+use ggez::{Context, GameResult};
+use ggez::error::GameError;
+
+use serde_json;
+
+use std::collections::HashMap;
+
+use fonts::TextCache;
+use {load_json, RELATIVE_X, RELATIVE_Y, STAGE_DOWN, STAGE_LEFT, STAGE_RIGHT};
+
+// 会話シーン
+// story.jsonの"dialogue"にステージ毎のシーンを書く
+//   "m_boss", "boss": Bossが出現する前
+//   "clear": ステージクリアの後
+//
+// Shotで次の行へ（文字を表示している途中なら全て表示）
+// Fast forwardを押している間は早送り, Skipでシーンを飛ばす
+// 会話の間はゲームのcountを進めない（リプレイには記録されず, 再生中は飛ばす）
+//
+// フォントはTextCacheで選ぶ（英数字だけの行はSoberbaSerif, 日本語の行はUnifont JP）
+
+const TEXT_SPEED: u32 = 2;
+// 1文字を表示するcount
+const FAST_LINE: u32 = 8;
+// 早送りで1行を表示するcount
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+	Left,
+	Right,
+}
+
+impl Default for Side {
+	fn default() -> Side {
+		Side::Left
+	}
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Line {
+	speaker: String,
+	#[serde(default)]
+	portrait: Option<String>,
+	// 立ち絵の画像
+	#[serde(default)]
+	emotion: Option<String>,
+	// 表情 "{portraitの拡張子を除いた名前}_{emotion}.png"があればそちらを使う
+	#[serde(default)]
+	side: Side,
+	// 立ち絵を出す側
+	text: String,
+	// "\n"で改行
+}
+
+pub type Scenes = HashMap<String, Vec<Line>>;
+
+// story.jsonからステージのシーンを読み込む
+pub fn load_scenes(stage_name: &str) -> GameResult<Scenes> {
+	let v = load_json("resources/story.json")?;
+	let dv = &v["dialogue"][stage_name];
+	if dv.is_null() {
+		return Ok(Scenes::new());
+	}
+	serde_json::from_value(dv.to_owned())
+		.map_err(|e| GameError::ResourceLoadError(format!("story.json: dialogue: {}", e)))
+}
+
+#[derive(Debug)]
+pub struct Dialogue {
+	lines: Vec<Line>,
+	index: usize,
+	// 表示している行
+	shown: usize,
+	// 表示した文字数
+	count: u32,
+	// 行を表示してからのcount
+	portraits: [Option<(String, String)>; 2],
+	// [左, 右] 最後に出した立ち絵 [表情の画像, 元の画像]
+	images: HashMap<String, Option<graphics::Image>>,
+}
+
+impl Dialogue {
+	pub fn new(lines: Vec<Line>) -> Dialogue {
+		let mut d = Dialogue {
+			lines,
+			index: 0,
+			shown: 0,
+			count: 0,
+			portraits: [None, None],
+			images: HashMap::new(),
+		};
+		d.show_portrait();
+		d
+	}
+
+	pub fn finished(&self) -> bool {
+		self.index >= self.lines.len()
+	}
+
+	fn line_len(&self) -> usize {
+		self.lines.get(self.index).map_or(0, |l| l.text.chars().count())
+	}
+
+	fn show_portrait(&mut self) {
+		if let Some(line) = self.lines.get(self.index) {
+			if line.portrait.is_some() {
+				let side = match line.side {
+					Side::Left => 0,
+					Side::Right => 1,
+				};
+				self.portraits[side] = Some(portrait_path(line));
+			}
+		}
+	}
+
+	fn next_line(&mut self) {
+		self.index += 1;
+		self.shown = 0;
+		self.count = 0;
+		self.show_portrait();
+	}
+
+	// 1count毎に呼ぶ fast: 早送り中
+	pub fn update(&mut self, fast: bool) {
+		if self.finished() {
+			return;
+		}
+		self.count += 1;
+		if fast {
+			self.shown = self.line_len();
+			if self.count >= FAST_LINE {
+				self.next_line();
+			}
+		} else if self.count % TEXT_SPEED == 0 {
+			self.shown = (self.shown + 1).min(self.line_len());
+		}
+	}
+
+	// 表示の途中なら行を全て表示, 表示し終わっていれば次の行
+	pub fn advance(&mut self) {
+		if self.shown < self.line_len() {
+			self.shown = self.line_len();
+		} else {
+			self.next_line();
+		}
+	}
+
+	pub fn skip(&mut self) {
+		self.index = self.lines.len();
+	}
+
+	fn image(&mut self, ctx: &mut Context, path: &str) -> Option<graphics::Image> {
+		self.images.entry(path.to_owned())
+			.or_insert_with(|| graphics::Image::new(ctx, path).ok())
+			.clone()
+	}
+
+	pub fn draw(&mut self, ctx: &mut Context, texts: &mut TextCache) -> GameResult<()> {
+		let line = match self.lines.get(self.index) {
+			Some(line) => line.clone(),
+			None => return Ok(()),
+		};
+
+		// 立ち絵 話していない側は暗くする
+		let active = match line.side {
+			Side::Left => 0,
+			Side::Right => 1,
+		};
+		for side in 0..2 {
+			let (with_emotion, base) = match self.portraits[side] {
+				Some(ref p) => p.clone(),
+				None => continue,
+			};
+			let image = match self.image(ctx, &with_emotion).or_else(|| self.image(ctx, &base)) {
+				Some(image) => image,
+				None => continue,
+			};
+			let c = if side == active { 255 } else { 110 };
+			let x = if side == 0 { STAGE_LEFT + 120.0 * RELATIVE_X } else { STAGE_RIGHT - 120.0 * RELATIVE_X };
+			let params = graphics::DrawParam {
+				dest: graphics::Point2::new(x, STAGE_DOWN - 200.0 * RELATIVE_Y),
+				offset: graphics::Point2::new(0.5, 1.0),
+				scale: graphics::Point2::new(RELATIVE_X, RELATIVE_Y),
+				color: Some(graphics::Color::from((c, c, c, 255))),
+				..Default::default()
+			};
+			graphics::draw_ex(ctx, &image, params)?;
+		}
+
+		// テキストボックス
+		let top = STAGE_DOWN - 200.0 * RELATIVE_Y;
+		graphics::set_color(ctx, graphics::Color::from((0, 0, 0, 190)))?;
+		graphics::rectangle(
+			ctx,
+			graphics::DrawMode::Fill,
+			graphics::Rect::new(STAGE_LEFT + 20.0 * RELATIVE_X, top, STAGE_RIGHT - STAGE_LEFT - 40.0 * RELATIVE_X, 180.0 * RELATIVE_Y),
+		)?;
+
+		let size = (24.0 * RELATIVE_X) as u32;
+		let left = STAGE_LEFT + 40.0 * RELATIVE_X;
+
+		graphics::set_color(ctx, graphics::Color::from((255, 230, 80, 255)))?;
+		let speaker = texts.text(ctx, &line.speaker, size)?;
+		graphics::draw(ctx, &speaker, graphics::Point2::new(left, top + 12.0 * RELATIVE_Y), 0.0)?;
+
+		graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))?;
+		let shown: String = line.text.chars().take(self.shown).collect();
+		for (i, row) in shown.split('\n').enumerate() {
+			if row.is_empty() {
+				continue;
+			}
+			let text = texts.text(ctx, row, size)?;
+			let y = top + (55.0 + 38.0 * i as f32) * RELATIVE_Y;
+			graphics::draw(ctx, &text, graphics::Point2::new(left, y), 0.0)?;
+		}
+		Ok(())
+	}
+}
+
+// [表情の画像, 元の画像] 表情の画像が無ければ元の画像を使う
+fn portrait_path(line: &Line) -> (String, String) {
+	let portrait = line.portrait.clone().unwrap_or_default();
+	let with_emotion = match (&line.emotion, portrait.rfind('.')) {
+		(&Some(ref emotion), Some(dot)) => format!("{}_{}{}", &portrait[..dot], emotion, &portrait[dot..]),
+		_ => portrait.clone(),
+	};
+	(with_emotion, portrait)
+}
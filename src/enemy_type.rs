@@ -0,0 +1,106 @@
+use ggez::{GameResult};
+use ggez::error::GameError;
+
+use serde_json;
+
+use std::collections::BTreeMap;
+
+use {load_json, EffectKind, ItemKind};
+
+// enemy_type.json の読み込み
+// char_type毎に 画像, 当たり判定, 体力, スコア, ドロップ, 撃破エフェクト を持つ
+//
+// story.json の char_type はここに登録された名前か、
+// 特殊な名前（SPECIAL_CHAR_TYPES）でなければならない
+
+// Enemy以外の用途で使われる char_type
+pub const SPECIAL_CHAR_TYPES: [&str; 3] = ["boss", "m_boss", "clear"];
+
+// 撃破時に落とすアイテム
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct DropElement {
+	pub item: ItemKind,
+	// アイテムの種類
+	pub num: u32,
+	// 個数
+	pub rate: f32,
+	// 落とす確率
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct EnemyType {
+	pub sprite: Option<String>,
+	// sprites.jsonのsheet 無い場合は円で描画
+	pub bbox_size: f32,
+	// 当たり判定の半径
+	pub life: f32,
+	// story.jsonでlifeを省略した時の体力
+	pub score: u32,
+	// 撃破時のスコア
+	pub drop: Vec<DropElement>,
+	// ドロップテーブル
+	#[serde(default)]
+	pub death_effect: Option<EffectKind>,
+	// 撃破時のエフェクト 無い場合はnull
+}
+
+#[derive(Debug, Clone)]
+pub struct EnemyTypes {
+	names: Vec<String>,
+	types: Vec<EnemyType>,
+}
+
+impl EnemyTypes {
+	pub fn load(path: &str) -> GameResult<EnemyTypes> {
+		let v = load_json(path)?;
+		// BTreeMapで読み込んでidの順番を固定する
+		let map: BTreeMap<String, EnemyType> = serde_json::from_value(v["enemy_type"].to_owned())
+			.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", path, e)))?;
+
+		let mut names = Vec::new();
+		let mut types = Vec::new();
+		for (name, et) in map {
+			EnemyTypes::validate(&name)?;
+			names.push(name);
+			types.push(et);
+		}
+
+		Ok(EnemyTypes {
+			names,
+			types,
+		})
+	}
+
+	fn validate(name: &str) -> GameResult<()> {
+		let err = |msg: String| {
+			Err(GameError::ResourceLoadError(format!("enemy_type \"{}\": {}", name, msg)))
+		};
+		if SPECIAL_CHAR_TYPES.contains(&name) {
+			return err("reserved char_type".to_owned());
+		}
+		Ok(())
+	}
+
+	pub fn get_id(&self, char_type: &str) -> Option<usize> {
+		self.names.iter().position(|n| n == char_type)
+	}
+
+	pub fn get(&self, id: usize) -> &EnemyType {
+		&self.types[id]
+	}
+
+	pub fn types(&self) -> &[EnemyType] {
+		&self.types
+	}
+
+	// story.jsonのchar_typeを確認
+	pub fn check_char_type(&self, char_type: &str) -> GameResult<()> {
+		if SPECIAL_CHAR_TYPES.contains(&char_type) || self.get_id(char_type).is_some() {
+			Ok(())
+		} else {
+			Err(GameError::ResourceLoadError(
+				format!("story.json: unknown char_type \"{}\"", char_type)
+			))
+		}
+	}
+}
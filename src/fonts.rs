@@ -0,0 +1,135 @@
+use ggez::graphics;
+use ggez::{Context, GameResult};
+
+use std::collections::HashMap;
+
+// フォントの選び方
+// CHAINの上から, 文字列の全ての文字を表示できて読み込めたフォントを使う
+// SoberbaSerifは英数字だけなので, ASCII以外の文字を含む文字列（日本語）はUnifont JPで表示する
+// Unifont JPはGNU Unifontから作った16pxのドット絵フォント（resources/fonts/README.md）
+// どれも読めなければggezの標準フォント
+// CHAINのフォントはresourcesに同梱する（tests/fonts.rsで確かめる）
+//
+// 毎frame読み込まないように, 描画ではTextCacheを使う
+
+pub const LATIN_FONT: &str = "/SoberbaSerif-Regular.ttf";
+pub const CHAIN: [(&str, bool); 2] = [
+	(LATIN_FONT, false),
+	("/fonts/unifont-jp.ttf", true),
+];
+// [ファイル, ASCII以外の文字も表示できる]
+
+pub fn needs_unicode(text: &str) -> bool {
+	!text.is_ascii()
+}
+
+pub fn font_for(ctx: &mut Context, text: &str, size: u32) -> GameResult<graphics::Font> {
+	let unicode = needs_unicode(text);
+	for &(path, has_unicode) in CHAIN.iter() {
+		if unicode && !has_unicode {
+			continue;
+		}
+		if let Ok(font) = graphics::Font::new(ctx, path, size) {
+			return Ok(font);
+		}
+	}
+	graphics::Font::default_font()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+	Left,
+	Center,
+	Right,
+}
+// 描画する点に文字列の左端, 中央, 右端を合わせる
+
+pub const OUTLINE_COLOR: graphics::Color = graphics::Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 };
+const OUTLINE_WIDTH: f32 = 1.5;
+const OUTLINE: [[f32; 2]; 8] = [
+	[-1.0, -1.0], [0.0, -1.0], [1.0, -1.0],
+	[-1.0, 0.0], [1.0, 0.0],
+	[-1.0, 1.0], [0.0, 1.0], [1.0, 1.0],
+];
+
+#[derive(Debug)]
+struct CachedText {
+	text: graphics::Text,
+	used: bool,
+	// 前のbegin_frameから使った
+}
+
+// 読み込んだフォントと作ったTextを覚えておく
+// フォントは大きさ毎に1回だけ読み込む
+// Textは同じ文字列, 大きさなら作り直さない 1frame使わなかったTextは捨てる
+#[derive(Debug, Default)]
+pub struct TextCache {
+	fonts: HashMap<(u32, bool), graphics::Font>,
+	// (大きさ, ASCII以外の文字を含む) -> フォント
+	texts: HashMap<(String, u32), CachedText>,
+}
+
+impl TextCache {
+	pub fn new() -> TextCache {
+		TextCache::default()
+	}
+
+	// 描画の最初に呼ぶ
+	pub fn begin_frame(&mut self) {
+		self.texts.retain(|_, t| t.used);
+		for t in self.texts.values_mut() {
+			t.used = false;
+		}
+	}
+
+	pub fn font(&mut self, ctx: &mut Context, text: &str, size: u32) -> GameResult<graphics::Font> {
+		let key = (size, needs_unicode(text));
+		if let Some(font) = self.fonts.get(&key) {
+			return Ok(font.clone());
+		}
+		let font = font_for(ctx, text, size)?;
+		self.fonts.insert(key, font.clone());
+		Ok(font)
+	}
+
+	pub fn text(&mut self, ctx: &mut Context, text: &str, size: u32) -> GameResult<graphics::Text> {
+		let key = (text.to_owned(), size);
+		if let Some(t) = self.texts.get_mut(&key) {
+			t.used = true;
+			return Ok(t.text.clone());
+		}
+		let font = self.font(ctx, text, size)?;
+		let t = graphics::Text::new(ctx, text, &font)?;
+		self.texts.insert(key, CachedText { text: t.clone(), used: true });
+		Ok(t)
+	}
+
+	// 今の色で描画する outline: 縁取りの色
+	pub fn draw(
+		&mut self,
+		ctx: &mut Context,
+		text: &str,
+		size: u32,
+		point: [f32; 2],
+		align: Align,
+		outline: Option<graphics::Color>,
+	) -> GameResult<()> {
+		let t = self.text(ctx, text, size)?;
+		let x = match align {
+			Align::Left => point[0],
+			Align::Center => point[0] - t.width() as f32 / 2.0,
+			Align::Right => point[0] - t.width() as f32,
+		};
+		if let Some(color) = outline {
+			for o in OUTLINE.iter() {
+				let params = graphics::DrawParam {
+					dest: graphics::Point2::new(x + o[0] * OUTLINE_WIDTH, point[1] + o[1] * OUTLINE_WIDTH),
+					color: Some(color),
+					..Default::default()
+				};
+				graphics::draw_ex(ctx, &t, params)?;
+			}
+		}
+		graphics::draw(ctx, &t, graphics::Point2::new(x, point[1]), 0.0)
+	}
+}
@@ -0,0 +1,123 @@
+use ggez::event::{Button, Keycode};
+use ggez::graphics;
+use ggez::{Context, GameResult};
+
+use bindings::{Action, Bindings};
+use fonts::{Align, TextCache};
+use locale::Strings;
+use menu::{Menu, MenuEvent};
+use {RELATIVE_X, RELATIVE_Y, SCREEN_WIDTH, SCREEN_HEIGHT};
+
+// Key config画面
+// TitleとPauseのOptionsから開き, Title, Pauseの上に重ねて描画する
+//
+// 操作を選ぶと次に押したkey（またはbutton）をその操作の先頭に割り当てる
+// 他の操作に割り当てられていたkeyはそちらから外す
+// 待っている間にEscapeを押すとやめる（Pauseを選んだ時は割り当てる）
+
+const RESET_ITEM: &str = "key_config.reset";
+const BACK_ITEM: &str = "key_config.back";
+
+#[derive(Debug, Clone)]
+pub struct KeyConfig {
+	menu: Menu,
+	waiting: Option<Action>,
+	// 割り当てるkeyを待っている操作
+	open: bool,
+}
+
+impl KeyConfig {
+	pub fn new() -> KeyConfig {
+		KeyConfig {
+			menu: Menu::new(&[]),
+			waiting: None,
+			open: false,
+		}
+	}
+
+	pub fn open(&mut self, bindings: &Bindings) {
+		self.set_labels(bindings);
+		self.waiting = None;
+		self.open = true;
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.open
+	}
+
+	fn set_labels(&mut self, bindings: &Bindings) {
+		let cursor = self.menu.cursor();
+		let mut items: Vec<String> = Action::ALL.iter().map(|a| a.key().to_owned()).collect();
+		items.push(RESET_ITEM.to_owned());
+		items.push(BACK_ITEM.to_owned());
+		self.menu.set_items(items);
+		for (i, a) in Action::ALL.iter().enumerate() {
+			self.menu.set_value(i, bindings.label(*a));
+		}
+		self.menu.set_cursor(cursor);
+	}
+
+	// 閉じた時はtrueを返す（設定を保存する）
+	pub fn input(&mut self, action: Action, bindings: &mut Bindings) -> bool {
+		match self.menu.input(action) {
+			MenuEvent::None => false,
+			MenuEvent::Confirm(i) if i < Action::ALL.len() => {
+				self.waiting = Some(Action::ALL[i]);
+				false
+			},
+			MenuEvent::Confirm(i) if i == Action::ALL.len() => {
+				*bindings = Bindings::default();
+				self.set_labels(bindings);
+				false
+			},
+			MenuEvent::Confirm(_) | MenuEvent::Cancel => {
+				self.open = false;
+				true
+			},
+		}
+	}
+
+	// 待っている時はkeycodeを割り当ててtrueを返す
+	pub fn bind_key(&mut self, keycode: Keycode, bindings: &mut Bindings) -> bool {
+		let action = match self.waiting.take() {
+			Some(a) => a,
+			None => return false,
+		};
+		if keycode != Keycode::Escape || action == Action::Pause {
+			bindings.bind_key(action, keycode);
+			self.set_labels(bindings);
+		}
+		true
+	}
+
+	pub fn bind_button(&mut self, button: Button, bindings: &mut Bindings) -> bool {
+		let action = match self.waiting.take() {
+			Some(a) => a,
+			None => return false,
+		};
+		bindings.bind_button(action, button);
+		self.set_labels(bindings);
+		true
+	}
+
+	pub fn draw(&self, ctx: &mut Context, strings: &Strings, texts: &mut TextCache) -> GameResult<()> {
+		graphics::set_color(ctx, graphics::Color::from((0, 0, 0, 220)))?;
+		graphics::rectangle(
+			ctx,
+			graphics::DrawMode::Fill,
+			graphics::Rect::new(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT),
+		)?;
+		graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))?;
+
+		texts.draw(ctx, strings.get("key_config.heading"), (40.0 * RELATIVE_X) as u32, [200.0 * RELATIVE_X, 120.0 * RELATIVE_Y], Align::Left, None)?;
+
+		let size = (24.0 * RELATIVE_X) as u32;
+		self.menu.draw(ctx, strings, texts, [200.0 * RELATIVE_X, 220.0 * RELATIVE_Y], size, 50.0 * RELATIVE_Y)?;
+
+		if let Some(action) = self.waiting {
+			let s = strings.format("key_config.waiting", &[strings.get(action.key())]);
+			texts.draw(ctx, &s, (22.0 * RELATIVE_X) as u32, [200.0 * RELATIVE_X, 840.0 * RELATIVE_Y], Align::Left, None)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,276 @@
+use ggez::graphics;
+use ggez::{Context, GameResult};
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+use bullet_type::BulletColor;
+use {RELATIVE_X, RELATIVE_Y, STAGE_UP, STAGE_DOWN, STAGE_LEFT, STAGE_RIGHT};
+
+// レーザー
+//
+// Straight: 予告線をwarning count表示した後, 太くなって当たり判定が出る
+//           duration count後に細くなって消える
+// Curvy: 先頭の弾の軌跡をなぞる 先頭が画面外に出ると尻尾から消える
+//
+// 角度はShotのvelocity[0]と同じ（0.0が真下, 1.0で半周）
+// 当たり判定は中心線（折れ線）からの距離で判定する
+
+const RAMP: u32 = 8;
+// 太くなる, 細くなるのにかけるcount
+const GRAZE_INTERVAL: u32 = 6;
+// かすり続けた時にGrazeを数える間隔
+
+#[derive(Debug, Clone)]
+enum LaserKind {
+	Straight {
+		origin: [f32; 2],
+		angle: f32,
+		angular_speed: f32,
+		// 1秒あたりの角度の変化
+		length: f32,
+		warning: u32,
+		duration: u32,
+	},
+	Curvy {
+		head: [f32; 2],
+		velocity: [f32; 2],
+		// [角度, スカラ値]
+		accel: [f32; 2],
+		// [角度, スカラ値] それぞれ加速
+		nodes: usize,
+		// 軌跡の最大の点の数
+		trail: VecDeque<[f32; 2]>,
+		// 先頭から順に並んだ軌跡
+		head_out: bool,
+		// 先頭が画面外に出た
+	},
+}
+
+#[derive(Debug, Clone)]
+pub struct Laser {
+	kind: LaserKind,
+	width: f32,
+	// 太さ（当たり判定は半分）
+	color: BulletColor,
+	count: u32,
+	graze_count: u32,
+	// 最後にGrazeしたcount
+	life: f32,
+}
+
+fn angle_vec(angle: f32) -> [f32; 2] {
+	let rad = angle * PI;
+	[rad.sin(), rad.cos()]
+}
+
+// 点pと線分abの距離
+fn segment_distance(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+	let ab = [b[0] - a[0], b[1] - a[1]];
+	let ap = [p[0] - a[0], p[1] - a[1]];
+	let len2 = ab[0] * ab[0] + ab[1] * ab[1];
+	let t = if len2 > 0.0 {
+		((ap[0] * ab[0] + ap[1] * ab[1]) / len2).max(0.0).min(1.0)
+	} else {
+		0.0
+	};
+	let dx = ap[0] - ab[0] * t;
+	let dy = ap[1] - ab[1] * t;
+	(dx * dx + dy * dy).sqrt()
+}
+
+fn out_of_stage(p: [f32; 2]) -> bool {
+	let mergin_x = 30.0 * RELATIVE_X;
+	let mergin_y = 30.0 * RELATIVE_Y;
+	p[0] < STAGE_LEFT - mergin_x || p[0] > STAGE_RIGHT + mergin_x
+		|| p[1] < STAGE_UP - mergin_y || p[1] > STAGE_DOWN + mergin_y
+}
+
+impl Laser {
+	pub fn straight(
+		origin: [f32; 2],
+		angle: f32,
+		length: f32,
+		width: f32,
+		warning: u32,
+		duration: u32,
+		color: BulletColor) -> Laser {
+		Laser {
+			kind: LaserKind::Straight {
+				origin,
+				angle,
+				angular_speed: 0.0,
+				length: length * RELATIVE_X,
+				warning,
+				duration,
+			},
+			width: width * RELATIVE_X,
+			color,
+			count: 0,
+			graze_count: 0,
+			life: 1.0,
+		}
+	}
+
+	// 回転するレーザー
+	pub fn with_angular_speed(mut self, speed: f32) -> Laser {
+		if let LaserKind::Straight { ref mut angular_speed, .. } = self.kind {
+			*angular_speed = speed;
+		}
+		self
+	}
+
+	pub fn curvy(
+		head: [f32; 2],
+		velocity: [f32; 2],
+		accel: [f32; 2],
+		nodes: usize,
+		width: f32,
+		color: BulletColor) -> Laser {
+		let mut trail = VecDeque::with_capacity(nodes);
+		trail.push_front(head);
+		Laser {
+			kind: LaserKind::Curvy {
+				head,
+				velocity,
+				accel,
+				nodes,
+				trail,
+				head_out: false,
+			},
+			width: width * RELATIVE_X,
+			color,
+			count: 0,
+			graze_count: 0,
+			life: 1.0,
+		}
+	}
+
+	pub fn alive(&self) -> bool {
+		self.life > 0.0
+	}
+
+	pub fn update(&mut self, dt: f32) {
+		self.count += 1;
+		let count = self.count;
+		match self.kind {
+			LaserKind::Straight { ref mut angle, angular_speed, warning, duration, .. } => {
+				*angle += angular_speed * dt;
+				if count >= warning + duration + RAMP * 2 {
+					self.life = 0.0;
+				}
+			},
+			LaserKind::Curvy { ref mut head, ref mut velocity, accel, nodes, ref mut trail, ref mut head_out } => {
+				if !*head_out {
+					velocity[0] += accel[0] * dt;
+					velocity[1] += accel[1] * dt;
+					let v = angle_vec(velocity[0]);
+					head[0] += v[0] * velocity[1] * RELATIVE_X * dt;
+					head[1] += v[1] * velocity[1] * RELATIVE_Y * dt;
+					*head_out = out_of_stage(*head);
+					trail.push_front(*head);
+				}
+				if trail.len() > nodes || *head_out {
+					trail.pop_back();
+				}
+				if trail.is_empty() {
+					self.life = 0.0;
+				}
+			},
+		}
+	}
+
+	// 太さの割合 0.0 ~ 1.0
+	fn width_rate(&self) -> f32 {
+		match self.kind {
+			LaserKind::Straight { warning, duration, .. } => {
+				let c = self.count;
+				if c < warning {
+					0.0
+				} else if c < warning + RAMP {
+					(c - warning) as f32 / RAMP as f32
+				} else if c < warning + RAMP + duration {
+					1.0
+				} else {
+					let end = warning + RAMP * 2 + duration;
+					end.saturating_sub(c) as f32 / RAMP as f32
+				}
+			},
+			LaserKind::Curvy { .. } => 1.0,
+		}
+	}
+
+	// 当たり判定があるか（予告線の間や細くなりきった時はない）
+	pub fn active(&self) -> bool {
+		self.width_rate() >= 0.5
+	}
+
+	// 中心線からの距離
+	pub fn distance(&self, p: [f32; 2]) -> f32 {
+		match self.kind {
+			LaserKind::Straight { origin, angle, length, .. } => {
+				let v = angle_vec(angle);
+				let end = [origin[0] + v[0] * length, origin[1] + v[1] * length];
+				segment_distance(p, origin, end)
+			},
+			LaserKind::Curvy { ref trail, .. } => {
+				let mut min = ::std::f32::MAX;
+				for i in 0..trail.len() {
+					let b = if i + 1 < trail.len() { trail[i + 1] } else { trail[i] };
+					min = min.min(segment_distance(p, trail[i], b));
+				}
+				min
+			},
+		}
+	}
+
+	// 円(p, r)との当たり判定
+	pub fn hit(&self, p: [f32; 2], r: f32) -> bool {
+		self.active() && self.distance(p) < self.width * self.width_rate() / 2.0 + r
+	}
+
+	// 円(p, r)がrangeの範囲にいればGRAZE_INTERVAL毎にtrue
+	pub fn graze(&mut self, p: [f32; 2], r: f32, range: f32) -> bool {
+		if !self.active() || self.count < self.graze_count + GRAZE_INTERVAL {
+			return false;
+		}
+		if self.distance(p) < self.width / 2.0 + r + range {
+			self.graze_count = self.count;
+			return true;
+		}
+		false
+	}
+
+	pub fn draw(&self, ctx: &mut Context) -> GameResult<()> {
+		let (r, g, b) = self.color.rgb();
+		let points: Vec<graphics::Point2> = match self.kind {
+			LaserKind::Straight { origin, angle, length, .. } => {
+				let v = angle_vec(angle);
+				vec![
+					graphics::Point2::new(origin[0], origin[1]),
+					graphics::Point2::new(origin[0] + v[0] * length, origin[1] + v[1] * length),
+				]
+			},
+			LaserKind::Curvy { ref trail, .. } => {
+				trail.iter().map(|p| graphics::Point2::new(p[0], p[1])).collect()
+			},
+		};
+		if points.len() < 2 {
+			return Ok(());
+		}
+
+		let rate = self.width_rate();
+		if rate == 0.0 {
+			// 予告線
+			graphics::set_color(ctx, graphics::Color::from((r, g, b, 160)))?;
+			graphics::line(ctx, &points, 1.5)?;
+		} else {
+			let width = self.width * rate;
+			graphics::set_color(ctx, graphics::Color::from((r, g, b, 200)))?;
+			graphics::line(ctx, &points, width)?;
+			graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 230)))?;
+			graphics::line(ctx, &points, width * 0.4)?;
+		}
+		graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))
+	}
+}
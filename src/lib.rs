@@ -6,19 +6,81 @@ extern crate ggez;
 extern crate rand;
 
 use ggez::graphics;
-use ggez::event::{Keycode, Mod};
-use ggez::timer;
+use ggez::event::{Axis, Button, Keycode, Mod};
 use ggez::{Context, ContextBuilder, GameResult};
 use ggez::error::GameError;
 
 use serde_json::Value;
 
+use std::collections::HashMap;
 use std::fs::File;
-use std::{path, env};
+use std::env;
 use std::io::Read;
-use rand::ThreadRng;
+use std::path::PathBuf;
+use rand::{Rng, SeedableRng};
+use rand::prng::XorShiftRng;
 
 pub mod shot_type;
+pub mod enemy_type;
+pub mod path;
+pub mod path_preview;
+pub mod boss;
+pub mod bullet_type;
+pub mod laser;
+pub mod shot_behavior;
+pub mod shot_spawn;
+pub mod bullet_store;
+pub mod scheduler;
+pub mod menu;
+pub mod pause;
+pub mod replay;
+pub mod mode;
+pub mod score;
+pub mod title;
+pub mod bindings;
+pub mod settings;
+pub mod key_config;
+pub mod options;
+pub mod audio;
+pub mod dialogue;
+pub mod locale;
+pub mod fonts;
+pub mod sprite;
+pub mod animation;
+pub mod background;
+pub mod particle;
+pub mod screen_fx;
+
+use enemy_type::{EnemyType, EnemyTypes};
+use path::PathElement;
+use boss::{BossData, BossState};
+use bullet_type::BulletTypes;
+use laser::Laser;
+use bullet_store::{Bullet, BulletStore};
+use scheduler::{Scheduler, StepPolicy};
+use pause::{PauseAction, PauseMenu};
+use replay::Replay;
+use mode::{Character, Difficulty, PracticeStart, SpellStart};
+use score::HighScores;
+use title::{TitleAction, TitleScreen};
+use bindings::{Action, Controls};
+use settings::Settings;
+use key_config::KeyConfig;
+use options::{OptionsAction, OptionsScreen};
+use audio::{Audio, Se};
+use dialogue::{Dialogue, Scenes};
+use locale::Strings;
+use fonts::{Align, TextCache, OUTLINE_COLOR};
+use sprite::{Blend, ImageId, Layer, Shape, SpriteRenderer};
+use animation::{Animator, SpriteSheets};
+use background::Background;
+use particle::Particles;
+use screen_fx::ScreenFx;
+use shot_type::Pattern;
+
+// 弾幕, ドロップの乱数
+// リプレイで同じ弾幕にするためにseedを決めて作る
+pub type GameRng = XorShiftRng;
 
 pub const SCREEN_WIDTH: f32 = 960.0;
 pub const SCREEN_HEIGHT: f32 = 720.0;
@@ -40,6 +102,15 @@ pub const STAGE_RIGHT: f32 = 830.0 * RELATIVE_X;
 // +---+-------+-----+ 960px
 // 0px 60px   830px 1280px
 
+const GRAZE_RANGE: f32 = 20.0;
+// 当たり判定からこの距離までをGrazeとする 音とパーティクルだけでスコアは増えない
+const PLAYER_SHEET: &str = "player";
+const ITEM_SHEET: &str = "item";
+// sprites.jsonのsheet
+const EMITTERS: [&str; 5] = ["enemy_death", "phase_break", "player_death", "bullet_cancel", "graze"];
+// particles.jsonのemitter
+const FADE_FRAMES: u32 = 30;
+// WindowStateを切り替える時のフェードのcount
 
 #[derive(Debug, PartialEq, Clone)]
 enum ActorType {
@@ -47,15 +118,44 @@ enum ActorType {
 	Enemy,
 	Boss,
 	PlShot,
-	EnShot,
 	Effect,
+	Item,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum PlayerStatus {
+	Normal,
+	Trans,
+	// 被弾, Bomb後の無敵
+}
+
+// エフェクトの種類
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectKind {
+	BombWave,
+	Burst,
+}
+
+// アイテムの種類
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+	Power,
+	Point,
+	Bomb,
+	Life,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 enum WindowState {
 	Title,
 	Gaming,
 	GamingBoss,
+	Pause,
+	// Gaming, GamingBossを止めている
+	Dialogue,
+	// 会話 countを進めない
 	GameOver,
 	GameClear,
 	ThankYouForPlaying,
@@ -66,6 +166,8 @@ pub struct Actor {
 	actor_type: ActorType,
 	point: [f32; 2],
 	// 位置 [x, y]
+	prev_point: [f32; 2],
+	// 前のcountの位置（補間描画用）
 	velocity: [f32; 2],
 	// 1秒の移動距離 [x, y]
 	// Shot: [Angle(0.0 <= x < 2.0, 真下が0, 右回り), scalar]
@@ -81,10 +183,24 @@ pub struct Actor {
 	// Playerは多分使わない
 	count: u32,
 	// 経過countメモ
-	// TODO: 実装タイミングが遅かったので書き換え必須
-	memo: String,
-	// メモ用
-	// Enemy: 放つ弾幕の種類を書く
+	status: PlayerStatus,
+	// Player: 無敵かどうか
+	pattern: Option<Pattern>,
+	// Enemy, Boss: 放つ弾幕
+	effect: Option<EffectKind>,
+	// Effect: エフェクトの種類
+	item: Option<ItemKind>,
+	// Item: アイテムの種類
+	type_id: usize,
+	// Enemy: EnemyTypesのid
+	path: Vec<PathElement>,
+	// 経路データ
+	path_origin: [f32; 2],
+	// 経路の開始位置
+	id: u32,
+	// Enemy, Boss: 出現順の番号（0は無し）
+	animator: Animator,
+	// Player, Enemy, Boss, Item: 再生中のアニメーション
 }
 
 impl Actor {
@@ -92,26 +208,44 @@ impl Actor {
 		Actor {
 			actor_type: ActorType::Player,
 			point: [440.0, 800.0],
+			prev_point: [440.0, 800.0],
 			velocity: [0.0; 2],
 			accel: [0.0, 0.0],
 			bbox_size: 5.0,
 			life: 3.0,
 			moving: Vec::new(),
 			count: 0,
-			memo: String::new(),
+			status: PlayerStatus::Normal,
+			pattern: None,
+			effect: None,
+			item: None,
+			type_id: 0,
+			path: Vec::new(),
+			path_origin: [0.0; 2],
+			id: 0,
+			animator: Animator::new(PLAYER_SHEET, "front"),
 		}
 	}
 	fn trans_pleyer_new(life: f32) -> Actor {
 		Actor {
 			actor_type: ActorType::Player,
 			point: [440.0, 800.0],
+			prev_point: [440.0, 800.0],
 			velocity: [0.0; 2],
 			accel: [0.0, 0.0],
 			bbox_size: 0.0,
 			life: life,
 			moving: Vec::new(),
 			count: 0,
-			memo: String::from("trans"),
+			status: PlayerStatus::Trans,
+			pattern: None,
+			effect: None,
+			item: None,
+			type_id: 0,
+			path: Vec::new(),
+			path_origin: [0.0; 2],
+			id: 0,
+			animator: Animator::new(PLAYER_SHEET, "front"),
 		}
 
 	}
@@ -119,65 +253,132 @@ impl Actor {
 		Actor {
 			actor_type: ActorType::PlShot,
 			point: p_point,
+			prev_point: p_point,
 			velocity: [1.0, 3000.0],
 			accel: [0.0, 0.0],
 			bbox_size: 10.0,
 			life: 1.0,
 			moving: Vec::new(),
 			count: 0,
-			memo: String::new(),
+			status: PlayerStatus::Normal,
+			pattern: None,
+			effect: None,
+			item: None,
+			type_id: 0,
+			path: Vec::new(),
+			path_origin: [0.0; 2],
+			id: 0,
+			animator: Animator::default(),
 		}
 	}
-	fn enemy_s_new(point: [f32; 2],vel: [f32; 2], life: f32, moving: Vec<MovingElement>) -> Actor {
+	fn enemy_new(
+		point: [f32; 2],
+		vel: [f32; 2],
+		life: f32,
+		moving: Vec<MovingElement>,
+		type_id: usize,
+		enemy_type: &EnemyType,
+		) -> Actor {
 		Actor {
 			actor_type: ActorType::Enemy,
 			point: point,
+			prev_point: point,
 			velocity: vel,
 			accel: [0.0; 2],
-			bbox_size: 20.0,
+			bbox_size: enemy_type.bbox_size,
 			life: life,
 			moving: moving,
 			count: 0,
-			memo: String::new(),
+			status: PlayerStatus::Normal,
+			pattern: None,
+			effect: None,
+			item: None,
+			type_id: type_id,
+			path: Vec::new(),
+			path_origin: [0.0; 2],
+			id: 0,
+			animator: enemy_type.sprite.as_ref().map_or_else(Animator::default, |s| Animator::new(s, "idle")),
 		}
 	}
-	fn boss_new(point: [f32; 2],vel: [f32; 2], life: f32, moving: Vec<MovingElement>, memo: &str) -> Actor {
+	fn boss_new(point: [f32; 2],vel: [f32; 2], life: f32, moving: Vec<MovingElement>, pattern: Option<Pattern>) -> Actor {
 		Actor {
 			actor_type: ActorType::Boss,
 			point: point,
+			prev_point: point,
 			velocity: vel,
 			accel: [0.0; 2],
 			bbox_size: 30.0,
 			life: life,
 			moving: moving,
 			count: 0,
-			memo: memo.to_owned()
+			status: PlayerStatus::Normal,
+			pattern: pattern,
+			effect: None,
+			item: None,
+			type_id: 0,
+			path: Vec::new(),
+			path_origin: [0.0; 2],
+			id: 0,
+			animator: Animator::default(),
 		}
 	}
-	fn enemy_shot_new(point: [f32; 2], velocity: [f32; 2]) -> Actor {
+	fn effect_new(point: [f32; 2], velocity: [f32; 2], moving: Vec<MovingElement>, effect: EffectKind) -> Actor {
 		Actor {
-			actor_type: ActorType::EnShot,
+			actor_type: ActorType::Effect,
 			point: point,
+			prev_point: point,
 			accel: [0.0; 2],
 			velocity: velocity,
 			bbox_size: 10.0,
 			life: 1.0,
-			moving: Vec::new(),
+			moving: moving,
 			count: 0,
-			memo: String::new(),
+			status: PlayerStatus::Normal,
+			pattern: None,
+			effect: Some(effect),
+			item: None,
+			type_id: 0,
+			path: Vec::new(),
+			path_origin: [0.0; 2],
+			id: 0,
+			animator: Animator::default(),
+		}
+	}
+	// movingの内容をcountに合わせて反映
+	fn apply_moving(actor: &mut Actor, count: u32) {
+		for m in 0..actor.moving.len() {
+			if actor.moving[m].count == count {
+				actor.accel = actor.moving[m].accel;
+				actor.pattern = actor.moving[m].shot_type;
+			}
+		}
+	}
+	// 経路中は経路に従い, それ以外は速度と加速度で動く
+	fn update_point_enemy(actor: &mut Actor, count: u32, dt: f32) {
+		if !path::update(actor, count, dt) {
+			Actor::update_point(actor, dt);
 		}
 	}
-	fn effect_new(point: [f32; 2], velocity: [f32; 2], moving: Vec<MovingElement>, memo: &str) -> Actor {
+	fn item_new(point: [f32; 2], velocity: [f32; 2], item: ItemKind) -> Actor {
 		Actor {
-			actor_type: ActorType::Effect,
+			actor_type: ActorType::Item,
 			point: point,
-			accel: [0.0; 2],
+			prev_point: point,
+			accel: [0.0, 300.0],
 			velocity: velocity,
-			bbox_size: 10.0,
+			bbox_size: 20.0,
 			life: 1.0,
-			moving: moving,
+			moving: Vec::new(),
 			count: 0,
-			memo: memo.to_owned(),
+			status: PlayerStatus::Normal,
+			pattern: None,
+			effect: None,
+			item: Some(item),
+			type_id: 0,
+			path: Vec::new(),
+			path_origin: [0.0; 2],
+			id: 0,
+			animator: Animator::new(ITEM_SHEET, "spin"),
 		}
 	}
 	fn update_point(actor: &mut Actor, dt: f32) {
@@ -222,47 +423,35 @@ impl Actor {
 		actor.point[1] += y_vel * dt;
 	}
 
-	fn update_point_shot(actor: &mut Actor, dt: f32) {
-		use std::f32::consts::PI;
-
-		let mergin_x = 30.0 * RELATIVE_X ;
-		let mergin_y = 30.0 * RELATIVE_Y ;
-		let s_up = STAGE_UP  - mergin_y;
-		let s_down = STAGE_DOWN  + mergin_y;
-		let s_left = STAGE_LEFT  - mergin_x;
-		let s_right = STAGE_RIGHT  + mergin_x;
-
-		let scalar = actor.accel[1];
-		let ragian = actor.accel[0] * PI;
-		let mut x_acc = scalar * ragian.sin();
-		let mut y_acc = scalar * ragian.cos();
-
-		let scalar = actor.velocity[1];
-		let ragian = actor.velocity[0] * PI;
-		let mut x_vel = scalar * ragian.sin();
-		let mut y_vel = scalar * ragian.cos();
+	fn update_point_item(actor: &mut Actor, dt: f32) {
+		// 一度上に跳ねてから落ちてくる
+		// 画面下に出たら消える
+		let max_fall = 200.0 * RELATIVE_Y;
+		actor.velocity[0] *= 0.95;
+		actor.velocity[1] += actor.accel[1] * RELATIVE_Y * dt;
+		if actor.velocity[1] > max_fall {
+			actor.velocity[1] = max_fall;
+		}
+		actor.point[0] += actor.velocity[0] * dt;
+		actor.point[1] += actor.velocity[1] * dt;
 
-		if (actor.point[0] < s_left && x_vel < 0.0) || (actor.point[0] > s_right && x_vel > 0.0) {
-			x_vel = 0.0;
+		if actor.point[1] > STAGE_DOWN + 30.0 * RELATIVE_Y {
 			actor.life = 0.0;
 		}
-		if (actor.point[1] < s_up && y_vel < 0.0) || (actor.point[1] > s_down && y_vel > 0.0) {
-			y_vel = 0.0;
+	}
+
+	fn update_point_shot(actor: &mut Actor, dt: f32) {
+		if !update_point_polar(&mut actor.point, &mut actor.velocity, actor.accel, dt) {
 			actor.life = 0.0;
 		}
-
-		actor.velocity[0] += actor.accel[0] * dt;
-		actor.velocity[1] += actor.accel[1] * dt;
-		actor.point[0] += x_vel * RELATIVE_X * dt;
-		actor.point[1] += y_vel * RELATIVE_Y * dt;
-
 	}
 
 	fn to_relative_window(mut self) -> Actor {
 		self.point[0] *= RELATIVE_X;
 		self.point[1] *= RELATIVE_Y;
+		self.prev_point = self.point;
 		match self.actor_type {
-			ActorType::PlShot | ActorType::EnShot => (),
+			ActorType::PlShot => (),
 			_ => {
 				self.velocity[0] *= RELATIVE_X ;
 				self.velocity[1] *= RELATIVE_Y ;
@@ -270,34 +459,70 @@ impl Actor {
 				self.accel[1] *= RELATIVE_Y ;
 			}
 		}
+		for pe in &mut self.path {
+			pe.scale(RELATIVE_X, RELATIVE_Y);
+		}
 		Actor {
 			actor_type: self.actor_type,
 			point: self.point,
+			prev_point: self.prev_point,
 			accel: self.accel,
 			velocity: self.velocity,
 			bbox_size: self.bbox_size,
 			life: self.life,
 			moving: self.moving,
 			count: self.count,
-			memo: self.memo,
+			status: self.status,
+			pattern: self.pattern,
+			effect: self.effect,
+			item: self.item,
+			type_id: self.type_id,
+			path: self.path,
+			path_origin: self.path_origin,
+			id: self.id,
+			animator: self.animator,
 		}
 	}
 }
 
-impl Default for Actor {
-	fn default() -> Actor {
-		Actor {
-			actor_type: ActorType::EnShot,
-			point: [0.0; 2],
-			velocity: [0.0; 2],
-			accel: [0.0; 2],
-			bbox_size: 10.0,
-			life: 1.0,
-			moving: Vec::new(),
-			count: 0,
-			memo: String::new(),
-		}
+// Shotを極座標の速度, 加速度で動かす
+// velocity, accel: [角度, スカラ値]
+// Stageの外へ出て行く時はfalse
+fn update_point_polar(point: &mut [f32; 2], velocity: &mut [f32; 2], accel: [f32; 2], dt: f32) -> bool {
+	use std::f32::consts::PI;
+
+	let mergin_x = 30.0 * RELATIVE_X ;
+	let mergin_y = 30.0 * RELATIVE_Y ;
+	let s_up = STAGE_UP  - mergin_y;
+	let s_down = STAGE_DOWN  + mergin_y;
+	let s_left = STAGE_LEFT  - mergin_x;
+	let s_right = STAGE_RIGHT  + mergin_x;
+
+	let scalar = velocity[1];
+	let ragian = velocity[0] * PI;
+	let mut x_vel = scalar * ragian.sin();
+	let mut y_vel = scalar * ragian.cos();
+	let mut inside = true;
+
+	if (point[0] < s_left && x_vel < 0.0) || (point[0] > s_right && x_vel > 0.0) {
+		x_vel = 0.0;
+		inside = false;
 	}
+	if (point[1] < s_up && y_vel < 0.0) || (point[1] > s_down && y_vel > 0.0) {
+		y_vel = 0.0;
+		inside = false;
+	}
+
+	velocity[0] += accel[0] * dt;
+	velocity[1] += accel[1] * dt;
+	point[0] += x_vel * RELATIVE_X * dt;
+	point[1] += y_vel * RELATIVE_Y * dt;
+	inside
+}
+
+// 前のcountと今のcountの間の描画位置
+fn lerp_point(prev: [f32; 2], point: [f32; 2], alpha: f32) -> [f32; 2] {
+	[prev[0] + (point[0] - prev[0]) * alpha, prev[1] + (point[1] - prev[1]) * alpha]
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -329,63 +554,108 @@ impl InputState {
 struct Assets {
 	frame_img: graphics::Image,
 	brack_out_img: graphics::Image,
-	player_front_img: graphics::Image,
-	player_right_img: graphics::Image,
-	player_left_img: graphics::Image,
-	effect_img: graphics::Image,
 	ending_img: graphics::Image,
+	sprites: SpriteRenderer,
+	// 以下はspritesに登録した画像
+	effect_img: ImageId,
+	sheet_imgs: HashMap<String, ImageId>,
+	// sprites.jsonのsheet -> 画像 画像の無いsheetは入っていない
+	bullet_img: ImageId,
 }
 
 impl Assets {
-	fn new(ctx: &mut Context) -> GameResult<Assets> {
+	fn new(ctx: &mut Context, sheets: &SpriteSheets, bullet_types: &BulletTypes) -> GameResult<Assets> {
 		let frame_img = graphics::Image::new(ctx, "/Frame.png").unwrap();
 		let brack_out_img = graphics::Image::new(ctx, "/brack_out.png").unwrap();
-		let player_front_img = graphics::Image::new(ctx,"/player_front.png").unwrap();
-		let player_left_img = graphics::Image::new(ctx,"/player_left.png").unwrap();
-		let player_right_img = graphics::Image::new(ctx,"/player_right.png").unwrap();
-		let effect_img = graphics::Image::new(ctx, "/effect.png").unwrap();
 		let ending_img = graphics::Image::new(ctx, "/thanks_sign.png").unwrap();
+		let mut sprites = SpriteRenderer::new(ctx)?;
+		let effect_img = sprites.add_image(graphics::Image::new(ctx, "/effect.png")?);
+		let mut sheet_imgs = HashMap::new();
+		// 同じ画像を使うsheetは1枚だけ読み込む
+		let mut loaded: HashMap<&str, ImageId> = HashMap::new();
+		for (name, sheet) in sheets.iter() {
+			if let Some(ref image) = sheet.image {
+				let id = match loaded.get(image.as_str()) {
+					Some(id) => *id,
+					None => sprites.add_image(graphics::Image::new(ctx, image)?),
+				};
+				loaded.insert(image, id);
+				sheet_imgs.insert(name.clone(), id);
+			}
+		}
+		let bullet_img = sprites.add_image(graphics::Image::new(ctx, &bullet_types.image)?);
 		Ok(Assets {
 			frame_img,
 			brack_out_img,
-			player_front_img,
-			player_right_img,
-			player_left_img,
-			effect_img,
 			ending_img,
+			sprites,
+			effect_img,
+			sheet_imgs,
+			bullet_img,
 		})
 	}
+
+	// 自機は他と重ならないようにすぐ描画する
 	fn draw_player (
+		&self,
 		ctx: &mut Context,
-		assets: &mut Assets,
+		sheets: &SpriteSheets,
 		player: &Actor,
-		input: InputState,
+		alpha: f32,
 		) -> GameResult<()> {
-		let mut img: &mut graphics::Image;
-		let mut front = false;
-		if input.right && input.left || front {
-			img = &mut assets.player_front_img;
-		} else {
-			if input.right {
-				img = &mut assets.player_right_img;
-			} else if input.left {
-				img = &mut assets.player_left_img;
-			} else {
-				img = &mut assets.player_front_img;
-			}
-		}
-		let point = {
-			let x = player.point[0];
-			let y = player.point[1];
-			graphics::Point2::new(x, y)
+		let name = player.animator.sheet();
+		let (sheet, frame, img) = match (sheets.get(name), player.animator.frame(sheets), self.sheet_imgs.get(name)) {
+			(Some(sheet), Some(frame), Some(img)) => (sheet, frame, *img),
+			_ => return Ok(()),
 		};
+		let p = lerp_point(player.prev_point, player.point, alpha);
 		let drawparams = graphics::DrawParam {
-			dest: point,
+			src: sheet.src(frame.frame),
+			dest: graphics::Point2::new(p[0], p[1]),
 			offset: graphics::Point2::new(0.5, 0.5),
-			scale: graphics::Point2::new(RELATIVE_X, RELATIVE_Y),
+			scale: graphics::Point2::new(RELATIVE_X * sheet.scale, RELATIVE_Y * sheet.scale),
 			..Default::default()
 		};
-		graphics::draw_ex(ctx, img, drawparams)
+		graphics::draw_ex(ctx, self.sprites.image(img), drawparams)
+	}
+
+	// 再生中のコマを予約する
+	// sheetが無ければshapeの大きさsize (px), sheetに画像が無ければshapeをアニメーションに合わせて回す
+	fn add_actor (
+		&mut self,
+		sheets: &SpriteSheets,
+		layer: Layer,
+		actor: &Actor,
+		alpha: f32,
+		shape: Shape,
+		size: [f32; 2],
+		color: graphics::Color,
+		) {
+		let p = lerp_point(actor.prev_point, actor.point, alpha);
+		let name = actor.animator.sheet();
+		let (sheet, frame) = match (sheets.get(name), actor.animator.frame(sheets)) {
+			(Some(sheet), Some(frame)) => (sheet, frame),
+			_ => {
+				self.sprites.add_shape(layer, shape, p, size, 0.0, color);
+				return;
+			},
+		};
+		let color = graphics::Color { a: color.a * frame.alpha, ..color };
+		match self.sheet_imgs.get(name) {
+			Some(img) => {
+				let drawparams = graphics::DrawParam {
+					src: sheet.src(frame.frame),
+					dest: graphics::Point2::new(p[0], p[1]),
+					rotation: frame.rotation,
+					offset: graphics::Point2::new(0.5, 0.5),
+					scale: graphics::Point2::new(RELATIVE_X * sheet.scale, RELATIVE_Y * sheet.scale),
+					color: Some(color),
+					..Default::default()
+				};
+				self.sprites.add(layer, *img, Blend::Alpha, drawparams);
+			},
+			None => self.sprites.add_shape(layer, shape, p, size, frame.rotation, color),
+		}
 	}
 }
 
@@ -406,10 +676,14 @@ struct StageFromJson {
 	velocity: [f32; 2],
 	// 初期速度
 	// Shot: [角度, スカラ値]
-	life: f32,
-	// 初期life
+	#[serde(default)]
+	life: Option<f32>,
+	// 初期life 省略時はenemy_type.jsonの値
 	moving: Vec<MovingElement>,
 	// 移動データ
+	#[serde(default)]
+	path: Vec<PathElement>,
+	// 経路データ
 }
 
 // 敵の出現, 行動のデータの構造体
@@ -428,13 +702,144 @@ struct Stage {
 	velocity: [f32; 2],
 	// 初期速度
 	// Shot: [角度, スカラ値]
-	life: f32,
-	// 初期life
+	life: Option<f32>,
+	// 初期life 省略時はenemy_type.jsonの値
 	moving: Vec<MovingElement>,
 	// 移動データ
+	path: Vec<PathElement>,
+	// 経路データ
+}
+
+impl Stage {
+	// Enemyを生成
+	fn spawn_enemy(&self, enemy_types: &EnemyTypes) -> Actor {
+		let p = [self.point[0], self.point[1]];
+		let v = [self.velocity[0], self.velocity[1]];
+		let m = self.moving.clone();
+		// char_typeは読み込み時に確認済み
+		let type_id = enemy_types.get_id(&self.char_type).unwrap();
+		let et = enemy_types.get(type_id);
+		let l = self.life.unwrap_or(et.life);
+		let mut enemy = Actor::enemy_new(p, v, l, m, type_id, et);
+		enemy.path = self.path.clone();
+		enemy.to_relative_window()
+	}
+
+	// 隊列の次のEnemyに進める
+	// 隊列が残っていなければfalse
+	fn next_in_formation(&mut self) -> bool {
+		if self.number_class[0] == 0 {
+			return false;
+		}
+		let add_count = self.number_class[1];
+		self.count += add_count;
+		self.number_class[0] -= 1;
+		self.point[0] += self.shift_point[0];
+		self.point[1] += self.shift_point[1];
+		// en date moving number
+		for edmn in 0..self.moving.len() {
+			self.moving[edmn].count += add_count;
+		}
+		for pe in &mut self.path {
+			pe.count += add_count;
+			pe.shift(self.shift_point);
+		}
+		true
+	}
+}
+
+// resourcesのjsonファイルを読み込む 読めなければpathを付けたResourceLoadError
+fn load_json(path: &str) -> GameResult<Value> {
+	let mut f = File::open(path)
+		.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", path, e)))?;
+	let mut s = String::new();
+	f.read_to_string(&mut s)
+		.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", path, e)))?;
+	serde_json::from_str(&s)
+		.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", path, e)))
+}
+
+// Practiceで選べるステージの区切り
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Section {
+	pub name: String,
+	pub count: u32,
+	// 区切りのcount この後のcountから始める
+}
 
+// story.jsonのsectionsからステージの区切りを読み込む
+// 書かれていなければinitial_countから始める1つだけ
+fn load_sections(stage_name: &str) -> GameResult<Vec<Section>> {
+	let v = load_json("resources/story.json")?;
+	let sv = &v["sections"][stage_name];
+	if sv.is_null() {
+		let initial_count = v["initial_count"].as_u64().unwrap_or(0) as u32;
+		return Ok(vec![Section { name: "Start".to_owned(), count: initial_count }]);
+	}
+	serde_json::from_value(sv.to_owned())
+		.map_err(|e| GameError::ResourceLoadError(format!("story.json: sections: {}", e)))
 }
 
+// story.json（path: story.jsonと同じ書き方のファイル）からステージを読み込む
+// [ステージ, initial_count]
+fn load_stage(path: &str, stage_name: &str, enemy_types: &EnemyTypes) -> GameResult<(Vec<Stage>, u32)> {
+	// JsonFileからDateを取得, 構造体型に変換
+	let v = load_json(path)?;
+	let sv: &Value = &v[stage_name];
+	// 未登録のshot_typeはここでエラーになる
+	let stage_from_json: Vec<StageFromJson> = serde_json::from_value(sv.to_owned())
+		.map_err(|e| GameError::ResourceLoadError(format!("{}: {}: {}", path, stage_name, e)))?;
+	// ---------------------
+
+	let initial_count = v["initial_count"].as_u64()
+		.ok_or_else(|| GameError::ResourceLoadError(format!("{}: initial_count is not found", path)))? as u32;
+
+	// 未登録のchar_type, 不正なpathを弾く
+	for sfj in &stage_from_json {
+		enemy_types.check_char_type(&sfj.char_type)?;
+		for pe in &sfj.path {
+			pe.validate()?;
+		}
+	}
+	// ---------------------
+
+	// StageFromJsonをStageに変換
+	//let mut buf_count: (u32, usize) = (stage_from_json[0].count, 0);
+	let mut stage: Vec<Stage> = Vec::new();
+	for sfj in stage_from_json {
+		for sfj_c in sfj.count {
+			stage.push(Stage {
+				count: sfj_c,
+				char_type: sfj.char_type.clone(),
+				number_class: sfj.number_class,
+				point: sfj.point,
+				shift_point: sfj.shift_point,
+				velocity: sfj.velocity,
+				life: sfj.life,
+				moving: sfj.moving.clone(),
+				path: sfj.path.clone(),
+			})
+		}
+	}
+	//println!("Inputed stage: {:?}", stage);
+	// ---------------------
+
+	// moving, path countにstage countを加算
+	// Jsonが書きやすくなる
+	for st in &mut stage {
+		for moving in &mut st.moving {
+			moving.count += st.count;
+		}
+		for pe in &mut st.path {
+			pe.count += st.count;
+		}
+	}
+	// ---------------------
+
+	Ok((stage, initial_count))
+}
+
+
 // Jsonから取り込んだ移動データ
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct MovingElement {
@@ -444,15 +849,16 @@ pub struct MovingElement {
 	accel: [f32; 2],
 	// 加速度 [x, y]
 	// Shot: [角度の値, スカラ値] （ベクトル演算ではない）
-	shot_type: String,
-	// 放つShotの種類
+	#[serde(deserialize_with = "shot_type::deserialize_optional")]
+	shot_type: Option<Pattern>,
+	// 放つShotの種類 "none"は撃たない
 }
 impl MovingElement {
-	fn new (count: u32, accel: [f32; 2], shot_type: &str) -> MovingElement{
+	fn new (count: u32, accel: [f32; 2], shot_type: Option<Pattern>) -> MovingElement{
 		MovingElement {
 			count: count,
 			accel: accel,
-			shot_type: shot_type.to_string(),
+			shot_type: shot_type,
 		}
 	}
 }
@@ -463,68 +869,146 @@ pub struct MainState {
 	player: Actor,
 	plshots: Vec<Actor>,
 	enemys: Vec<Actor>,
+	dying: Vec<Actor>,
+	// 倒された敵 deathのアニメーションが終わるまで描画する
 	boss: Vec<Actor>,
-	enshots: Vec<Actor>,
+	enshots: BulletStore,
+	shot_buf: Vec<Bullet>,
+	// このcountに生成された子弾
+	parents: Vec<(u32, [f32; 2])>,
+	// Orbitで回る相手の位置 [id, 位置]
+	lasers: Vec<Laser>,
 	effects: Vec<Actor>,
 	stage: Vec<Stage>,
+	stage_start: Vec<Stage>,
+	// Restart用の読み込んだままのstage
+	initial_count: u32,
 	input: InputState,
 	input_break: InputState,
 	game_count: [u32; 2],
 	// [道中, Boss]
 	//rand_v: Vec<f32>,
-	rand: ThreadRng,
+	rand: GameRng,
 	assets: Assets,
+	sheets: SpriteSheets,
+	particles: Particles,
+	enemy_types: EnemyTypes,
+	boss_data: HashMap<String, BossData>,
+	boss_state: Option<BossState>,
+	bullet_types: BulletTypes,
+	items: Vec<Actor>,
 	bomb: u32,
+	power: u32,
 	score: u32,
+	next_id: u32,
+	// 次に出現するEnemy, Bossのid
+	scheduler: Scheduler,
+	show_overlay: bool,
+	// FPSなどの表示
+	title: TitleScreen,
+	pause: PauseMenu,
+	resume_state: WindowState,
+	// Pauseを閉じた時に戻るWindowState
+	difficulty: Difficulty,
+	character: Character,
+	practice: Option<PracticeStart>,
+	// Practiceの開始条件 スコアを別に記録する
+	spell: Option<SpellStart>,
+	// Spell practiceで練習しているphase
+	spell_miss: bool,
+	// Spell practiceで被弾, Bombをした
+	replay: Replay,
+	playback: Option<Replay>,
+	// 再生中のリプレイ
+	playback_frame: usize,
+	// 次に再生するcount
+	high_scores: HighScores,
+	scores_path: PathBuf,
+	replay_dir: PathBuf,
+	// リプレイを保存するディレクトリ
+	controls: Controls,
+	// 押しているkey, buttonとキー設定
+	key_config: KeyConfig,
+	options: OptionsScreen,
+	settings: Settings,
+	settings_path: PathBuf,
+	audio: Audio,
+	scenes: Scenes,
+	// story.jsonの会話 [場面, 台詞]
+	dialogue: Option<Dialogue>,
+	dialogue_next: WindowState,
+	// 会話が終わった後のWindowState
+	strings: Strings,
+	// 設定の言語の文字列
+	texts: TextCache,
+	background: Background,
+	// ステージの背景
+	fx: ScreenFx,
+	// 画面の揺れ, フラッシュ, フェード
 }
 
 impl MainState {
 	pub fn new(ctx: &mut Context) -> GameResult<MainState> {
 
-		// JsonFileからDateを取得, 構造体型に変換
-		let mut f = File::open("resources/story.json").expect("open json file");
-		let mut s = String::new();
-		f.read_to_string(&mut s).expect("read json to string");
+		// enemy_type.json, story.jsonを読み込む
+		let enemy_types = EnemyTypes::load("resources/enemy_type.json")?;
+		let (stage1, initial_count) = load_stage("resources/story.json", "stage1", &enemy_types)?;
+		let sections = load_sections("stage1")?;
+		// ---------------------
 
-		let v: Value = serde_json::from_str(&s).expect("serde json from str");
-		let sv: &Value = &v["stage1"];
-		let mut stage_from_json: Vec<StageFromJson>
-			= serde_json::from_value(sv.to_owned()).expect("serde json from value");
+		// boss.jsonを読み込み, story.jsonのBossが登録されているか確認
+		let boss_data = boss::load("resources/boss.json")?;
+		let bullet_types = BulletTypes::load("resources/bullet_type.json")?;
+		for st in &stage1 {
+			let is_boss = st.char_type == "boss" || st.char_type == "m_boss";
+			if is_boss && !boss_data.contains_key(&st.char_type) {
+				return Err(GameError::ResourceLoadError(
+					format!("boss.json: \"{}\" is not defined", st.char_type)
+				));
+			}
+		}
 		// ---------------------
 
-		let initial_count = v["initial_count"].as_u64().unwrap() as u32;
-
-		// StageFromJsonをStageに変換
-		//let mut buf_count: (u32, usize) = (stage_from_json[0].count, 0);
-		let mut stage1: Vec<Stage> = Vec::new();
-		for sfj in stage_from_json {
-			for sfj_c in sfj.count {
-				stage1.push(Stage {
-					count: sfj_c,
-					char_type: sfj.char_type.clone(),
-					number_class: sfj.number_class,
-					point: sfj.point,
-					shift_point: sfj.shift_point,
-					velocity: sfj.velocity,
-					life: sfj.life,
-					moving: sfj.moving.clone(),
-				})
+		// sprites.jsonを読み込み, 使うsheetとanimationがあるか確認
+		// Bossのsheetは無くてもよい（円で描画）
+		let sheets = SpriteSheets::load("resources/sprites.json")?;
+		sheets.require(PLAYER_SHEET, &[
+			"front", "bank_left", "left", "unbank_left", "bank_right", "right", "unbank_right",
+		])?;
+		sheets.require(ITEM_SHEET, &["spin"])?;
+		for et in enemy_types.types() {
+			if let Some(ref sheet) = et.sprite {
+				sheets.require(sheet, &["idle"])?;
+			}
+		}
+		for name in boss_data.keys() {
+			if sheets.get(name).is_some() {
+				sheets.require(name, &["idle", "cast"])?;
 			}
 		}
-		//println!("Inputed stage: {:?}", stage1);
 		// ---------------------
 
-		// moving countにstage countを加算
-		// Jsonが書きやすくなる
-		for stage in &mut stage1 {
-			for moving in &mut stage.moving {
-				moving.count += stage.count;
+		let particles = Particles::new(particle::load("resources/particles.json")?);
+		particles.require(&EMITTERS)?;
+
+		// Spell practiceで選べるphase
+		// story.jsonに出てくる順
+		let mut spells = Vec::new();
+		let mut boss_names: Vec<&str> = Vec::new();
+		for st in &stage1 {
+			let name = st.char_type.as_str();
+			if boss_data.contains_key(name) && !boss_names.contains(&name) {
+				boss_names.push(name);
+				for (n, (i, p)) in boss_data[name].spells().into_iter().enumerate() {
+					let spell = SpellStart { boss: name.to_owned(), phase: i };
+					spells.push((spell, format!("{} {}: {}", name, n + 1, p.name())));
+				}
 			}
 		}
 		// ---------------------
 
 		// 乱数の配列を作成
-		let rng = rand::thread_rng();
+		let rng = GameRng::seed_from_u64(0);
 		//let mut rand_v = vec![0.0; 100];
 		//for i in rand_v.iter_mut() {
 		//	*i = rng.gen();
@@ -532,33 +1016,533 @@ impl MainState {
 		//println!("rand_v: {:?}", rand_v);
 		// --------------------
 
-		let s = MainState{
+		let settings_path = Settings::path(ctx);
+		let settings = Settings::load(&settings_path);
+		let scores_path = HighScores::path(ctx);
+		let replay_dir = Replay::dir(ctx);
+		settings.apply_window(ctx)?;
+		let mut audio = Audio::new(ctx, audio::load("resources/audio.json")?);
+		audio.set_volume(settings.bgm_volume, settings.se_volume);
+		audio.play_bgm("title");
+		let strings = Strings::load(settings.language)?;
+		let background = Background::new(ctx, background::load_background("stage1")?);
+		let settings_effects = settings.screen_effects;
+
+		let mut s = MainState{
 			window_state: WindowState::Title,
 			player: Actor::player_new().to_relative_window(),
 			plshots: Vec::with_capacity(50),
 			enemys: Vec::with_capacity(30),
+			dying: Vec::with_capacity(30),
 			boss: Vec::with_capacity(1),
-			enshots: Vec::with_capacity(100),
+			enshots: BulletStore::with_capacity(4096),
+			shot_buf: Vec::with_capacity(1024),
+			parents: Vec::with_capacity(64),
+			lasers: Vec::with_capacity(10),
 			effects: Vec::with_capacity(30),
-			stage: stage1,
+			stage: stage1.clone(),
+			stage_start: stage1,
+			initial_count,
 			input: InputState::new(),
 			input_break: InputState::new(),
 			//rand_v: rand_v,
 			rand: rng,
 			game_count: [initial_count, 0],
-			assets: Assets::new(ctx).unwrap(),
+			assets: Assets::new(ctx, &sheets, &bullet_types)?,
+			sheets,
+			particles,
+			bullet_types: bullet_types,
+			enemy_types: enemy_types,
+			boss_data: boss_data,
+			boss_state: None,
+			items: Vec::with_capacity(100),
 			bomb: 4,
+			power: 0,
 			score: 0,
+			next_id: 1,
+			scheduler: Scheduler::new(StepPolicy::Slowdown),
+			show_overlay: false,
+			title: TitleScreen::new(sections, spells, replay_dir.clone()),
+			pause: PauseMenu::new(),
+			resume_state: WindowState::Gaming,
+			difficulty: Difficulty::default(),
+			character: Character::default(),
+			practice: None,
+			spell: None,
+			spell_miss: false,
+			replay: Replay::new("stage1", initial_count, 0, Difficulty::default(), Character::default(), None, None),
+			playback: None,
+			playback_frame: 0,
+			high_scores: HighScores::load(&scores_path),
+			scores_path,
+			replay_dir,
+			controls: Controls::new(settings.bindings.clone()),
+			key_config: KeyConfig::new(),
+			options: OptionsScreen::new(),
+			settings,
+			settings_path,
+			audio,
+			scenes: dialogue::load_scenes("stage1")?,
+			dialogue: None,
+			dialogue_next: WindowState::Gaming,
+			strings,
+			texts: TextCache::new(),
+			background,
+			fx: ScreenFx::new(settings_effects),
 		};
 
+		s.title.set_difficulty(s.settings.difficulty);
 		Ok(s)
 	}
+
+	// 補間描画用に今の位置を残す
+	fn store_prev_points(&mut self) {
+		self.player.prev_point = self.player.point;
+		for a in self.plshots.iter_mut()
+			.chain(self.enemys.iter_mut())
+			.chain(self.boss.iter_mut())
+			.chain(self.items.iter_mut()) {
+			a.prev_point = a.point;
+		}
+	}
+
+	// 道中の最初（Practiceではsection）からやり直す
+	// seed: 弾幕, ドロップの乱数
+	// WindowStateはGaming（Spell practiceではGamingBoss）になる
+	fn reset_game(&mut self, seed: u64) {
+		self.window_state = WindowState::Gaming;
+		self.rand = GameRng::seed_from_u64(seed);
+		self.player = Actor::player_new().to_relative_window();
+		self.player.life = self.difficulty.lives();
+		self.plshots.clear();
+		self.enemys.clear();
+		self.dying.clear();
+		self.boss.clear();
+		self.boss_state = None;
+		self.enshots.clear();
+		self.enshots.set_speed_scale(self.difficulty.bullet_speed());
+		self.shot_buf.clear();
+		self.lasers.clear();
+		self.effects.clear();
+		self.particles.clear();
+		self.fx.clear();
+		self.fx.fade_in(FADE_FRAMES);
+		self.items.clear();
+		self.stage = self.stage_start.clone();
+		self.game_count = [self.initial_count, 0];
+		self.input_break = InputState::new();
+		self.bomb = self.difficulty.bombs();
+		self.dialogue = None;
+		self.power = 0;
+		self.score = 0;
+		self.next_id = 1;
+
+		let mut start_count = self.initial_count;
+		if let Some(ref p) = self.practice {
+			start_count = p.count;
+			self.game_count[0] = p.count;
+			self.player.life = p.lives as f32;
+			self.bomb = p.bombs;
+			// sectionより前に出現するEnemyを飛ばす
+			for st in &mut self.stage {
+				while st.count <= p.count && st.next_in_formation() {}
+			}
+		}
+		// Spell practiceは道中を飛ばして, 1つのphaseだけのBossを出す
+		if let Some(spell) = self.spell.clone() {
+			self.stage.clear();
+			let mut bd = self.boss_data[&spell.boss].clone();
+			bd.phases = vec![bd.phases[spell.phase].clone()];
+			let point = self.stage_start.iter()
+				.find(|st| st.char_type == spell.boss)
+				.map_or(bd.home, |st| st.point);
+			// 背景はBossが出現するcountまで進める
+			let count = self.stage_start.iter()
+				.find(|st| st.char_type == spell.boss)
+				.map_or(start_count, |st| st.count);
+			self.background.reset(count);
+			self.spawn_boss(&spell.boss, point, bd);
+			self.spell_miss = false;
+		} else {
+			self.background.reset(start_count);
+			self.audio.play_bgm("stage1");
+		}
+
+		let practice = self.practice.clone();
+		let spell = self.spell.clone();
+		self.replay = Replay::new("stage1", start_count, seed, self.difficulty, self.character, practice, spell);
+	}
+
+	// 会話があれば今のWindowStateを止めて会話を始める
+	// リプレイの再生中, Spell practiceでは会話を飛ばす
+	fn start_scene(&mut self, name: &str) {
+		if self.playback.is_some() || self.spell.is_some() {
+			return;
+		}
+		if let Some(lines) = self.scenes.get(name) {
+			if !lines.is_empty() {
+				self.dialogue = Some(Dialogue::new(lines.clone()));
+				self.dialogue_next = self.window_state;
+				self.window_state = WindowState::Dialogue;
+			}
+		}
+	}
+
+	// Bossを出現させてGamingBossにする
+	// phaseの内容はboss.jsonから取得
+	fn spawn_boss(&mut self, name: &str, point: [f32; 2], bd: BossData) {
+		self.audio.play_bgm(name);
+		if bd.phases[0].shot_type.is_some() {
+			self.audio.play_se(Se::SpellDeclare);
+		}
+		self.window_state = WindowState::GamingBoss;
+		self.enemys = Vec::new();
+		self.enshots.clear();
+		self.lasers = Vec::new();
+		self.game_count[1] = 1;
+		let mut boss = Actor::boss_new(
+					point,
+					[0.0, 0.0],
+					bd.phases[0].life,
+					bd.moving(),
+					bd.phases[0].shot_type,
+				).to_relative_window();
+		boss.bbox_size = bd.bbox_size;
+		// sheetがあれば登場時にcastを再生する
+		if self.sheets.get(name).is_some() {
+			boss.animator = Animator::new(name, "cast");
+		}
+		boss.id = self.next_id;
+		self.next_id += 1;
+		self.boss_state = Some(BossState::new(bd, &mut boss));
+		self.boss.push(boss);
+	}
+
+	fn start_game(&mut self, difficulty: Difficulty, character: Character, practice: Option<PracticeStart>) {
+		self.difficulty = difficulty;
+		self.character = character;
+		self.practice = practice;
+		self.spell = None;
+		self.playback = None;
+		let seed = rand::thread_rng().gen::<u64>();
+		self.reset_game(seed);
+	}
+
+	// reset_gameでBossを出してGamingBossになる
+	fn start_spell(&mut self, difficulty: Difficulty, character: Character, spell: SpellStart) {
+		self.difficulty = difficulty;
+		self.character = character;
+		self.practice = None;
+		self.playback = None;
+		self.high_scores.add_spell_attempt(&spell, difficulty);
+		self.save_scores();
+		self.spell = Some(spell);
+		let seed = rand::thread_rng().gen::<u64>();
+		self.reset_game(seed);
+	}
+
+	// Spell practiceを始める（コマンドライン用）
+	// spell: "Boss名:phase" phaseは弾幕のあるphaseの1からの番号かshot_typeの名前 (boss:b_6rotate_4rand, m_boss:1)
+	pub fn practice_spell(&mut self, spell: &str, difficulty: Difficulty) -> GameResult<()> {
+		let mut it = spell.splitn(2, ':');
+		let boss = it.next().unwrap_or("");
+		let phase = it.next().unwrap_or("1");
+		let bd = self.boss_data.get(boss).ok_or_else(|| {
+			GameError::UnknownError(format!("spell: unknown boss \"{}\"", boss))
+		})?;
+		let spells = bd.spells();
+		let index = match phase.parse::<usize>() {
+			Ok(n) if n >= 1 && n <= spells.len() => Some(spells[n - 1].0),
+			Ok(_) => None,
+			Err(_) => spells.iter().find(|s| s.1.name() == phase).map(|s| s.0),
+		};
+		let index = index.ok_or_else(|| {
+			GameError::UnknownError(format!("spell: \"{}\" has no phase \"{}\"", boss, phase))
+		})?;
+		let spell = SpellStart { boss: boss.to_owned(), phase: index };
+		self.start_spell(difficulty, Character::default(), spell);
+		Ok(())
+	}
+
+	fn start_playback(&mut self, replay: Replay) {
+		self.difficulty = replay.difficulty();
+		self.character = replay.character();
+		self.practice = replay.practice().cloned();
+		self.spell = replay.spell().cloned();
+		self.reset_game(replay.seed());
+		self.playback = Some(replay);
+		self.playback_frame = 0;
+	}
+
+	fn return_to_title(&mut self) {
+		self.playback = None;
+		self.practice = None;
+		self.spell = None;
+		self.input = InputState::new();
+		self.reset_game(0);
+		self.audio.play_bgm("title");
+		self.title.open();
+		self.window_state = WindowState::Title;
+	}
+
+	// GameOver, GameClearの時にハイスコアを記録
+	// Practiceは別に記録し, リプレイの再生中は記録しない
+	fn record_score(&mut self, cleared: bool) {
+		if self.playback.is_some() || self.spell.is_some() {
+			return;
+		}
+		let section = self.practice.as_ref().map(|p| p.section.clone());
+		self.high_scores.add(self.score, self.difficulty, self.character, cleared, section);
+		self.save_scores();
+	}
+
+	// 音声デバイスを使わない（--no-audio）
+	pub fn disable_audio(&mut self) {
+		self.audio.mute();
+	}
+
+	// パーティクルを出さない（--no-particles）
+	pub fn disable_particles(&mut self) {
+		self.particles.set_enabled(false);
+	}
+
+	fn save_settings(&mut self) {
+		self.settings.bindings = self.controls.bindings.clone();
+		if let Err(e) = self.settings.save(&self.settings_path) {
+			eprintln!("Could not save settings: {}", e);
+		}
+	}
+
+	fn save_scores(&self) {
+		if let Err(e) = self.high_scores.save(&self.scores_path) {
+			eprintln!("Could not save scores: {}", e);
+		}
+	}
+
+	fn title_action(&mut self, ctx: &mut Context, action: TitleAction) {
+		match action {
+			TitleAction::None => (),
+			TitleAction::Start { difficulty, character, practice } => self.start_game(difficulty, character, practice),
+			TitleAction::Spell { difficulty, character, spell } => self.start_spell(difficulty, character, spell),
+			TitleAction::Replay(path) => match Replay::load(&path) {
+				Ok(replay) => self.start_playback(replay),
+				Err(e) => eprintln!("Could not load replay: {}", e),
+			},
+			TitleAction::Options => self.open_options(),
+			TitleAction::Quit => {
+				let _ = ctx.quit();
+			},
+		}
+	}
+
+	// Gaming, GamingBossの時だけ止める
+	// リプレイの再生中は止めない
+	fn pause_game(&mut self) {
+		if self.playback.is_some() {
+			return;
+		}
+		match self.window_state {
+			WindowState::Gaming | WindowState::GamingBoss | WindowState::Dialogue => {
+				self.resume_state = self.window_state;
+				self.window_state = WindowState::Pause;
+				self.pause.open();
+				self.audio.set_paused(true);
+			},
+			_ => (),
+		}
+	}
+
+	fn pause_action(&mut self, action: PauseAction) {
+		match action {
+			PauseAction::None => (),
+			PauseAction::Resume => self.window_state = self.resume_state,
+			PauseAction::Restart => {
+				let (d, c, p) = (self.difficulty, self.character, self.practice.clone());
+				match self.spell.clone() {
+					Some(spell) => self.start_spell(d, c, spell),
+					None => self.start_game(d, c, p),
+				}
+			},
+			PauseAction::Title => self.return_to_title(),
+			PauseAction::SaveReplay => {
+				let message = match self.replay.save(&self.replay_dir) {
+					Ok(path) => self.strings.format("pause.saved", &[&path]),
+					Err(e) => self.strings.format("pause.save_failed", &[&e.to_string()]),
+				};
+				self.pause.set_message(message);
+			},
+			PauseAction::Options => self.open_options(),
+		}
+		self.audio.set_paused(self.window_state == WindowState::Pause);
+	}
+
+	// 閉じるkeyはShotの最初のkey
+	fn close_window_text(&self) -> String {
+		let key = self.controls.bindings.keys.get(&Action::Shot)
+			.and_then(|k| k.first())
+			.map_or("Z", |k| k.as_str());
+		self.strings.format("screen.close_window", &[key])
+	}
+
+	fn open_options(&mut self) {
+		self.options.open();
+		self.options.set_values(&self.settings, self.show_overlay, self.scheduler.policy, &self.strings);
+	}
+
+	fn options_action(&mut self, ctx: &mut Context, action: Action) {
+		let window = (self.settings.window_scale, self.settings.fullscreen, self.settings.vsync);
+		match self.options.input(action, &mut self.settings) {
+			OptionsAction::None | OptionsAction::Close => (),
+			OptionsAction::Changed => {
+				if window != (self.settings.window_scale, self.settings.fullscreen, self.settings.vsync) {
+					if let Err(e) = self.settings.apply_window(ctx) {
+						eprintln!("Could not change window: {}", e);
+					}
+				}
+				if self.settings.language != self.strings.language() {
+					match Strings::load(self.settings.language) {
+						Ok(strings) => self.strings = strings,
+						Err(e) => eprintln!("Could not load strings: {}", e),
+					}
+				}
+				self.title.set_difficulty(self.settings.difficulty);
+				self.audio.set_volume(self.settings.bgm_volume, self.settings.se_volume);
+				self.fx.set_intensity(self.settings.screen_effects);
+				self.save_settings();
+			},
+			OptionsAction::ToggleOverlay => self.show_overlay = !self.show_overlay,
+			OptionsAction::NextPolicy => self.scheduler.policy = self.scheduler.policy.next(),
+			OptionsAction::KeyConfig => self.key_config.open(&self.controls.bindings),
+		}
+		self.options.set_values(&self.settings, self.show_overlay, self.scheduler.policy, &self.strings);
+	}
+
+	// 押した操作をKey config, メニュー, ゲームへ渡す
+	fn action_down(&mut self, ctx: &mut Context, action: Action) {
+		if self.key_config.is_open() {
+			if self.key_config.input(action, &mut self.controls.bindings) {
+				self.save_settings();
+			}
+			return;
+		}
+		if self.options.is_open() {
+			self.options_action(ctx, action);
+			return;
+		}
+		match self.window_state {
+			WindowState::Title => {
+				let action = self.title.input(action, &self.high_scores);
+				self.title_action(ctx, action);
+				return;
+			},
+			WindowState::Pause => {
+				let action = self.pause.input(action);
+				self.pause_action(action);
+				return;
+			},
+			_ => (),
+		}
+		if let Some(ref mut d) = self.dialogue {
+			match action {
+				Action::Shot => d.advance(),
+				Action::Skip => d.skip(),
+				_ => (),
+			}
+		}
+		// 再生中はPauseでTitleへ戻る
+		if action == Action::Pause {
+			if self.playback.is_some() {
+				self.return_to_title();
+			} else {
+				self.pause_game();
+			}
+		}
+	}
+
+	// 押しているkey, buttonからゲームの入力を作る
+	// 再生中はリプレイの入力を使う
+	fn update_input(&mut self) {
+		if self.playback.is_some() {
+			return;
+		}
+		self.input = self.controls.state();
+		// 離した時にShotの押しっぱなし判定を戻す
+		if !self.input.shot {
+			self.input_break.shot = false;
+		}
+	}
+
+	fn defeat_enemy(&mut self, mut enemy: Actor) {
+		let point = enemy.point;
+		let et = self.enemy_types.get(enemy.type_id);
+		self.score += et.score;
+		self.audio.play_se(Se::EnemyDeath);
+
+		// ドロップテーブルからアイテムを生成
+		for d in &et.drop {
+			if self.rand.gen::<f32>() >= d.rate {
+				continue;
+			}
+			for _ in 0..d.num {
+				let vx = (self.rand.gen::<f32>() - 0.5) * 200.0 * RELATIVE_X;
+				let vy = -(100.0 + self.rand.gen::<f32>() * 100.0) * RELATIVE_Y;
+				self.items.push(Actor::item_new(point, [vx, vy], d.item));
+			}
+		}
+
+		if let Some(effect) = et.death_effect {
+			self.effects.push(Actor::effect_new(point, [0.0; 2], Vec::new(), effect));
+		}
+		self.particles.emit("enemy_death", point);
+
+		let has_death = self.sheets.get(enemy.animator.sheet()).map_or(false, |s| s.has("death"));
+		if has_death {
+			enemy.animator.play("death");
+			self.dying.push(enemy);
+		}
+	}
+
+	// 1count毎にアニメーションを進める
+	// 自機は左右の入力で傾ける（bank_left -> left, 離すとunbank_left -> front）
+	fn update_animations(&mut self) {
+		let current = self.player.animator.animation();
+		let next = if self.input.left && !self.input.right {
+			match current {
+				"bank_left" | "left" => None,
+				_ => Some("bank_left"),
+			}
+		} else if self.input.right && !self.input.left {
+			match current {
+				"bank_right" | "right" => None,
+				_ => Some("bank_right"),
+			}
+		} else {
+			match current {
+				"bank_left" | "left" => Some("unbank_left"),
+				"bank_right" | "right" => Some("unbank_right"),
+				_ => None,
+			}
+		};
+		if let Some(next) = next {
+			self.player.animator.play(next);
+		}
+
+		let sheets = &self.sheets;
+		self.player.animator.update(sheets);
+		for act in self.enemys.iter_mut()
+			.chain(self.dying.iter_mut())
+			.chain(self.boss.iter_mut())
+			.chain(self.items.iter_mut())
+		{
+			act.animator.update(sheets);
+		}
+		self.dying.retain(|act| !act.animator.finished(sheets));
+	}
 }
 
 impl ggez::event::EventHandler for MainState {
 	fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-		const FPS: u32 = 60;
-		let seconds = 1.0 / FPS as f32;
+		let seconds = self.scheduler.dt();
 
 		//println!("");
 		//println!("{:?}", timer::duration_to_f64(timer::get_delta(ctx)));
@@ -571,155 +1555,212 @@ impl ggez::event::EventHandler for MainState {
 		// -------------------------
 
 
-		while timer::check_update_time(ctx, FPS) {
+		let steps = self.scheduler.begin_frame();
+		for _ in 0..steps {
 			//println!("{:?}", self.enemys);
 			let mut game_count_use = 0;
+			self.store_prev_points();
+			self.audio.update(ctx, seconds);
 
-			// WindowStateの分岐----------
+			// 止めていない間の入力だけを記録
+			// 再生中は記録した入力を使う
+			let mut playback_end = false;
 			match self.window_state {
-				WindowState::Title => {
-					// InputStateのbreak----------
-					if self.input_break.shot {
-						self.input.shot = false;
-					}
-					if self.input.shot || self.input_break.shot {
-						self.input_break.shot = true;
-					}
-					// ---------------------
-
-					if self.input.shot {
-						self.window_state = WindowState::Gaming;
-					}
-					continue
+				WindowState::Gaming | WindowState::GamingBoss => match self.playback {
+					Some(ref pb) => match pb.input(self.playback_frame) {
+						Some(input) => {
+							self.input = input;
+							self.playback_frame += 1;
+						},
+						None => playback_end = true,
+					},
+					None => self.replay.record(&self.input),
+				},
+				WindowState::GameOver | WindowState::GameClear => {
+					playback_end = self.playback.is_some() && self.game_count[0] >= 180;
 				},
+				_ => (),
+			}
+			if playback_end {
+				self.return_to_title();
+				continue;
+			}
+
+			// 画面効果----------
+			// Pause中は止める
+			if self.window_state != WindowState::Pause {
+				self.fx.update();
+			}
+			// -------------------------
+
+			// 背景のスクロール----------
+			// Boss戦の間はcountが止まるので, Boss戦のイベントは出現した時に起こる
+			match self.window_state {
+				WindowState::Gaming | WindowState::GamingBoss => self.background.update(self.game_count[0], seconds),
+				_ => (),
+			}
+			// -------------------------
+
+			// WindowStateの分岐----------
+			match self.window_state {
+				// メニューはkey_down_eventで動かす
+				WindowState::Title => continue,
 				WindowState::Gaming => {
 					self.game_count[0] += 1;
 					game_count_use = self.game_count[0];
 
 					// Jsonから取得したデータから、Enemyを生成
 					// stage element number
+					let mut boss_spawn = None;
+					let mut clear = false;
 					'stage: for st in &mut self.stage {
 						if st.count == self.game_count[0] {
 							match st.char_type.as_str() {
 								"clear" => {
+									self.record_score(true);
+									self.audio.stop_bgm();
 									self.enemys = Vec::new();
 									self.window_state = WindowState::GameClear;
 									self.game_count[0] = 0;
+									clear = true;
 									break 'stage;
 								}
 								b_type @  "boss" | b_type @ "m_boss" => {
 									// char_type = "boss"が見つかった場合、WindowStateがGamingBossになる
-									// Window切り替え
-									self.window_state = WindowState::GamingBoss;
-									self.game_count[0] += 1;
-									// -------------------------
-
-									// GamingBoss 初期化
-									self.enemys = Vec::new();
-									self.enshots = Vec::new();
-									let mut boss_moving: Vec<MovingElement>;
-									let ac = [0.0; 2];
-									match b_type {
-										"m_boss" => {
-											boss_moving = vec![
-												MovingElement::new(30,[0.0; 2], "waiting"),
-												MovingElement::new(1500, [0.0; 2], "m_six_rotate"),
-												MovingElement::new(1500, [0.0; 2], "m_six_fireflower"),
-											];
-										},
-										"boss" => {
-											boss_moving = vec![
-												MovingElement::new(30, [0.0; 2],"waiting"),
-												MovingElement::new(1500, [0.0; 2], "b_normal"),
-												MovingElement::new(1500, [0.0; 2], "b_6rotate_4rand"),
-												MovingElement::new(1500, [0.0; 2], "b_normal"),
-												MovingElement::new(1500, [0.0; 2], "b_2fireflower_4pdis"),
-												MovingElement::new(1500, [0.0; 2], "b_normal"),
-												MovingElement::new(1500, [0.0; 2], "b_6carpet_fireflower"),
-											];
-										},
-										_ => boss_moving = Vec::new(),
-									}
-									self.boss.push(Actor::boss_new(
-												[450.0, 200.0],
-												[0.0, 0.0],
-												0.1,
-												boss_moving,
-												"",
-											).to_relative_window());
-									// -------------------------
+									boss_spawn = Some((b_type.to_owned(), st.point));
 									break 'stage;
 								}
 								_ => (),
 							}
 							// --------------------
-							let p = [st.point[0], st.point[1]];
-							let v = [st.velocity[0], st.velocity[1]];
-							let l = st.life;
-							let m = st.moving.clone();
-							self.enemys.push(Actor::enemy_s_new(p, v, l, m).to_relative_window());
-
-							if st.number_class[0] > 0 {
-								let add_count = st.number_class[1];
-								st.count += add_count;
-								st.number_class[0] -= 1;
-								st.point[0] += st.shift_point[0];
-								st.point[1] += st.shift_point[1];
-								// en date moving number
-								for edmn in 0..st.moving.len() {
-									st.moving[edmn].count += add_count;
-								}
-							}
+							let mut enemy = st.spawn_enemy(&self.enemy_types);
+							enemy.id = self.next_id;
+							self.next_id += 1;
+							self.enemys.push(enemy);
+							st.next_in_formation();
 						}
 					}
 					self.stage.retain(|c| c.number_class[0] >= 0);
+					if let Some((b_type, point)) = boss_spawn {
+						self.game_count[0] += 1;
+						let bd = self.boss_data[&b_type].clone();
+						self.spawn_boss(&b_type, point, bd);
+						self.start_scene(&b_type);
+					}
+					if clear {
+						self.start_scene("clear");
+					}
 					// --------------------
 
 				}
 				WindowState::GamingBoss => {
-					game_count_use = self.game_count[1];
-
-
-					// Update boss counter----------
-					self.game_count[1] += 1;
+					// Bossの移動----------
+					// 登場, 中央へ戻る, 退場の間は弾幕を撃たない
+					let bst = self.boss_state.as_mut().unwrap();
+					let attacking = bst.update(&mut self.boss[0], self.player.point, &mut self.rand, seconds);
+					if bst.finished() {
+						self.boss = Vec::new();
+						self.boss_state = None;
+						self.window_state = WindowState::Gaming;
+						// Spell practiceは同じphaseを繰り返す
+						if let Some(spell) = self.spell.clone() {
+							if self.playback.is_some() {
+								self.return_to_title();
+							} else {
+								let (d, c) = (self.difficulty, self.character);
+								self.start_spell(d, c, spell);
+							}
+						}
+						continue;
+					}
+					if !attacking {
+						game_count_use = bst.motion_count();
+					}
 					// -------------------------
 
-					// Bossの更新
-					// BossShotをmatch分岐で生成
-					//println!("{:?}", self.boss);
-					let mut bs = &mut self.boss;
-					let pp = self.player.point;
-					let mut es = &mut self.enshots;
-					let gc = game_count_use;
-					let rn = &self.rand;
-					match bs[0].memo.as_str() {
-						"m_six_rotate" => shot_type::m_six_rotate(&mut bs[0], pp, es, gc),
-						"m_six_fireflower" => shot_type::m_six_fireflower(&mut bs[0], pp, es, gc),
-						"b_normal" => shot_type::b_normal(&mut bs[0], pp, es, gc, rn),
-						"b_6rotate_4rand" => shot_type::b_6rotate_4rand(&mut bs[0], pp, es, gc, rn),
-						"b_2fireflower_4pdis" => shot_type::b_2fireflower_4pdis(&mut bs[0], pp, es, gc, rn),
-						"b_6carpet_fireflower" => shot_type::b_6carpet_fireflower(&mut bs[0], pp, es, gc, rn),
-						_ => (),
-						//_ => println!("no shot"),
-					}
+					let mut spell_capture = false;
+					if attacking {
+						game_count_use = self.game_count[1];
+						if self.player.status == PlayerStatus::Trans {
+							// 被弾, Bombで取得失敗
+							self.spell_miss = true;
+						}
+
+						// Update boss counter----------
+						self.game_count[1] += 1;
+						// -------------------------
+
+						// Bossの更新
+						// BossShotをmatch分岐で生成
+						//println!("{:?}", self.boss);
+						let mut bs = &mut self.boss;
+						let pp = self.player.point;
+						let mut es = &mut self.enshots;
+						let ls = &mut self.lasers;
+						let gc = game_count_use;
+						let rn = &mut self.rand;
+						if let Some(pattern) = bs[0].pattern {
+							shot_type::fire(pattern, &mut bs[0], pp, es, ls, gc, rn);
+						}
 
-					if bs[0].life < 0.0 || game_count_use == bs[0].moving[0].count {
-						if bs[0].moving.len() > 1 {
-							self.game_count[1] = 1;
-							bs[0].life = 500.0;
-							*es = Vec::new();
-							bs[0].moving.remove(0);
-							bs[0].memo = bs[0].moving[0].shot_type.clone();
-						} else {
-							//println!("boss end");
-							*bs = Vec::new();
-							*es = Vec::new();
-							self.window_state = WindowState::Gaming;
+						if bs[0].life < 0.0 || game_count_use == bs[0].moving[0].count {
+							spell_capture = self.spell.is_some() && bs[0].life < 0.0 && !self.spell_miss;
+							// phaseの終わりに弾, レーザーを消す
+							if bs[0].pattern.is_some() {
+								self.particles.emit("phase_break", bs[0].point);
+								self.fx.shake(12.0, 40);
+							}
+							for b in es.iter() {
+								self.particles.emit("bullet_cancel", b.point);
+							}
+							es.clear();
+							*ls = Vec::new();
+							// 最後のphaseなら退場を始める
+							if bst.next_phase(&mut bs[0]) {
+								self.game_count[1] = 1;
+								bs[0].life = bst.phase_life();
+								bs[0].moving.remove(0);
+								bs[0].pattern = bs[0].moving[0].shot_type;
+								if bs[0].pattern.is_some() {
+									self.audio.play_se(Se::SpellDeclare);
+									bs[0].animator.play("cast");
+								}
+							}
 						}
 					}
+					if spell_capture && self.playback.is_none() {
+						let spell = self.spell.clone().unwrap();
+						self.high_scores.add_spell_capture(&spell, self.difficulty);
+						self.save_scores();
+					}
 					// -------------------------
 				}
+				WindowState::Pause => continue,
+				WindowState::Dialogue => {
+					let fast = self.controls.is_held(Action::FastForward);
+					let finished = match self.dialogue {
+						Some(ref mut d) => {
+							d.update(fast);
+							d.finished()
+						},
+						None => true,
+					};
+					if finished {
+						self.dialogue = None;
+						self.window_state = self.dialogue_next;
+					}
+					continue;
+				},
 				WindowState::GameOver => {
+					// Spell practiceは同じphaseからやり直す
+					if let Some(spell) = self.spell.clone() {
+						if self.playback.is_none() && self.game_count[0] >= 120 {
+							let (d, c) = (self.difficulty, self.character);
+							self.start_spell(d, c, spell);
+							continue;
+						}
+					}
+
 					// InputStateのbreak----------
 					if self.input_break.shot {
 						self.input.shot = false;
@@ -737,9 +1778,14 @@ impl ggez::event::EventHandler for MainState {
 				},
 				WindowState::GameClear => {
 					self.game_count[0] += 1;
+					// 暗くしてからThankYouForPlayingへ
+					if self.game_count[0] == 180 - FADE_FRAMES {
+						self.fx.fade_out(FADE_FRAMES);
+					}
 					if self.game_count[0] >= 180 {
 						self.game_count[0] = 0;
 						self.window_state = WindowState::ThankYouForPlaying;
+						self.fx.fade_in(FADE_FRAMES);
 					}
 				},
 				WindowState::ThankYouForPlaying => {
@@ -785,19 +1831,22 @@ impl ggez::event::EventHandler for MainState {
 				self.player.velocity[0] = 0.0;
 			}
 
+			let speed = self.character.speed();
 			if !self.input.shift {
 				// 高速移動
-				self.player.velocity[0] *= 350.0;
-				self.player.velocity[1] *= 350.0;
+				self.player.velocity[0] *= speed[0];
+				self.player.velocity[1] *= speed[0];
 			} else {
 				// 低速Shift移動
-				self.player.velocity[0] *= 150.0;
-				self.player.velocity[1] *= 150.0;
+				self.player.velocity[0] *= speed[1];
+				self.player.velocity[1] *= speed[1];
 			}
 
 			Actor::update_point(&mut self.player, seconds);
 
 			if self.player.life <= 0.0 {
+				self.record_score(false);
+				self.audio.stop_bgm();
 				self.window_state = WindowState::GameOver;
 				self.game_count[0] = 0;
 			}
@@ -806,6 +1855,7 @@ impl ggez::event::EventHandler for MainState {
 
 			// Update Plshot state----------
 			if self.input.shot && game_count_use % 3 == 0 {
+				self.audio.play_se(Se::Shot);
 				let mut pp = self.player.point;
 				pp[0] += 20.0 * RELATIVE_X;
 				self.plshots.push(Actor::player_shot_new(pp));
@@ -819,10 +1869,12 @@ impl ggez::event::EventHandler for MainState {
 
 			// Bomb ----------
 			if self.input.bomb && !self.input_break.bomb && self.bomb > 0 {
-				self.player.memo = "trans".to_owned();
+				self.player.status = PlayerStatus::Trans;
 				self.input_break.bomb = true;
 				self.bomb -= 1;
-				self.effects.push(Actor::effect_new(self.player.point, [0.0; 2], Vec::new(), "bomb_wave"))
+				self.audio.play_se(Se::Bomb);
+				self.fx.flash(0.8, 40);
+				self.effects.push(Actor::effect_new(self.player.point, [0.0; 2], Vec::new(), EffectKind::BombWave))
 			}
 			// -------------------------
 
@@ -830,30 +1882,27 @@ impl ggez::event::EventHandler for MainState {
 			// - Jsonから取得したデータから、Enemyの動作を書き換え
 			// - 弾幕を張る
 			// - 位置の更新
+			let mut defeated: Vec<Actor> = Vec::new();
+			// 倒されたEnemy
 			for e in &mut self.enemys {
 				if self.input_break.bomb {
 					e.life = 0.0;
-					self.score += 30;
+					defeated.push(e.clone());
 					continue;
 				}
-				for m in 0..e.moving.len() {
-					if e.moving[m].count == self.game_count[0] {
-						e.accel = e.moving[m].accel;
-						e.memo = e.moving[m].shot_type.clone();
-					}
-				}
+				e.count += 1;
+				Actor::apply_moving(e, self.game_count[0]);
 
 				let pp = self.player.point;
 				let mut es = &mut self.enshots;
+				let ls = &mut self.lasers;
 				let gc = game_count_use;
-				let rn = &self.rand;
-				match e.memo.as_str() {
-					"six" => shot_type::n_six(e, pp, es, gc),
-					"four-two_disp"=> shot_type::n_four_two_disp(e, pp, es, rn),
-					_ => (),
+				let rn = &mut self.rand;
+				if let Some(pattern) = e.pattern {
+					shot_type::fire(pattern, e, pp, es, ls, gc, rn);
 				}
 
-				Actor::update_point(e, seconds);
+				Actor::update_point_enemy(e, self.game_count[0], seconds);
 			}
 			//-------------------------
 
@@ -871,44 +1920,86 @@ impl ggez::event::EventHandler for MainState {
 
 			// Update EnShot----------
 			// Hit EnemyShots & Player
-			for es in &mut self.enshots {
-				es.count += 1;
-				if self.input_break.bomb {
-					es.life = 0.0;
-					continue;
+			self.parents.clear();
+			self.parents.extend(self.enemys.iter().chain(self.boss.iter()).map(|a| (a.id, a.point)));
+			if self.input_break.bomb {
+				for b in self.enshots.iter() {
+					self.particles.emit("bullet_cancel", b.point);
 				}
-				for esm in es.moving.iter() {
-					if esm.count == es.count {
-						es.accel = esm.accel;
-						es.memo = esm.shot_type.clone();
-					}
-				}
-				Actor::update_point_shot(es, seconds);
-
+				self.enshots.clear();
+			}
+			self.enshots.update(self.player.point, &self.parents, &mut self.shot_buf, seconds);
+			for es in self.enshots.iter_mut() {
 				let pl = &mut self.player;
-				if pl.memo != "trans".to_owned() && in_bbox(pl, es) {
-					es.life = 0.0;
+				let hit = self.bullet_types.hit(es.style, es.point, es.velocity[0], pl.point, pl.bbox_size);
+				if pl.status != PlayerStatus::Trans && hit {
+					es.alive = false;
 					pl.life -= 1.0;
+					self.audio.play_se(Se::PlayerDeath);
+					self.particles.emit("player_death", pl.point);
+					self.fx.shake(16.0, 30);
 					*pl = Actor::trans_pleyer_new(pl.life).to_relative_window();
+				} else if pl.status != PlayerStatus::Trans && !es.grazed {
+					let graze_r = pl.bbox_size + GRAZE_RANGE;
+					if self.bullet_types.hit(es.style, es.point, es.velocity[0], pl.point, graze_r) {
+						es.grazed = true;
+						self.audio.play_se(Se::Graze);
+						self.particles.emit("graze", es.point);
+					}
 				}
 			}
+			self.enshots.sweep();
+			self.enshots.append(&mut self.shot_buf);
 			let mut p_count = self.player.count;
-			if self.player.memo == "trans".to_owned() {
+			if self.player.status == PlayerStatus::Trans {
 				self.player.count += 1;
 				if self.player.count > 180 {
-					self.player.memo = "".to_owned();
+					self.player.status = PlayerStatus::Normal;
 					self.input_break.bomb = false;
 					self.player.count= 0;
 				}
 			}
 			// -------------------------
 
+			// Update Laser----------
+			// Hit Laser & Player
+			// Bombで消える
+			if self.input_break.bomb {
+				self.lasers = Vec::new();
+			}
+			for ls in &mut self.lasers {
+				ls.update(seconds);
+
+				let pl = &mut self.player;
+				if pl.status == PlayerStatus::Trans {
+					continue;
+				}
+				if ls.hit(pl.point, pl.bbox_size) {
+					pl.life -= 1.0;
+					self.audio.play_se(Se::PlayerDeath);
+					self.particles.emit("player_death", pl.point);
+					self.fx.shake(16.0, 30);
+					*pl = Actor::trans_pleyer_new(pl.life).to_relative_window();
+				} else if ls.graze(pl.point, pl.bbox_size, GRAZE_RANGE) {
+					self.audio.play_se(Se::Graze);
+					self.particles.emit("graze", pl.point);
+				}
+			}
+			self.lasers.retain(|l| l.alive());
+			// -------------------------
+
 			// Hit PlayerShots & Boss----------
+			// 登場, 退場中などは当たらない
+			let boss_vulnerable = self.boss_state.as_ref().map_or(false, |b| b.vulnerable());
 			for bs in &mut self.boss {
+				if !boss_vulnerable {
+					break;
+				}
 				for ps in &mut self.plshots {
 					if in_bbox(bs, ps) {
 						bs.life -= ps.life;
 						ps.life = 0.0;
+						self.audio.play_se(Se::Hit);
 						if bs.life < 0.0 {
 							self.score += 30;
 						}
@@ -920,58 +2011,97 @@ impl ggez::event::EventHandler for MainState {
 			// Hit PlayerShots & Enemy, Player & Enemy----------
 			for en in &mut self.enemys {
 				for ps in &mut self.plshots {
-					if in_bbox(en, ps) {
+					if en.life > 0.0 && in_bbox(en, ps) {
 						en.life -= ps.life;
 						ps.life = 0.0;
+						self.audio.play_se(Se::Hit);
 						if en.life <= 0.0 {
-							self.score += 30;
+							defeated.push(en.clone());
 						}
 					}
 				}
 
-				if self.player.memo != "trans".to_owned() && in_bbox(&self.player, en) {
+				if self.player.status != PlayerStatus::Trans && in_bbox(&self.player, en) {
 					self.player.life -= 1.0;
+					self.audio.play_se(Se::PlayerDeath);
+					self.particles.emit("player_death", self.player.point);
+					self.fx.shake(16.0, 30);
 					self.player = Actor::trans_pleyer_new(self.player.life).to_relative_window();
 				}
 			}
 			// -------------------------
 
+			// Defeated Enemy----------
+			// スコア, ドロップ, エフェクト
+			for en in defeated {
+				self.defeat_enemy(en);
+			}
+			// -------------------------
+
+			// Update Item----------
+			// Playerに触れたアイテムを回収
+			for it in &mut self.items {
+				Actor::update_point_item(it, seconds);
+				if in_bbox(&self.player, it) {
+					it.life = 0.0;
+					self.audio.play_se(Se::Item);
+					match it.item {
+						Some(ItemKind::Power) => self.power += 1,
+						Some(ItemKind::Point) => self.score += 100,
+						Some(ItemKind::Bomb) => self.bomb += 1,
+						Some(ItemKind::Life) => self.player.life += 1.0,
+						None => (),
+					}
+				}
+			}
+			// -------------------------
+
 			// Update Bomb----------
 			for ef in &mut self.effects {
-				match ef.memo.as_str() {
-					"bomb_wave" => {
+				match ef.effect {
+					Some(EffectKind::BombWave) => {
 						ef.count += 1;
 						if ef.count >= 180 {
 							ef.life = 0.0;
 						}
 					}
-					_ => (),
+					Some(EffectKind::Burst) => {
+						ef.count += 1;
+						if ef.count >= 30 {
+							ef.life = 0.0;
+						}
+					}
+					None => (),
 				}
 			}
 			// -------------------------
 
+			// Update Particle----------
+			self.particles.update(seconds);
+			// -------------------------
+
+			// Update Animation----------
+			self.update_animations();
+			// -------------------------
+
 			// Clear zero_life Enemy, Shot----------
 			self.plshots.retain(|s| s.life > 0.0);
 			self.enemys.retain(|s| s.life > 0.0);
-			self.enshots.retain(|s| s.life > 0.0);
 			self.effects.retain(|s| s.life > 0.0);
+			self.items.retain(|s| s.life > 0.0);
 			// -------------------------
 		}
+		self.scheduler.end_frame();
 		Ok(())
 	}
 
 	fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
 		graphics::clear(ctx);
+		self.texts.begin_frame();
 
-		let pl_point = self.player.point;
+		let alpha = self.scheduler.alpha();
+		let pl_point = lerp_point(self.player.prev_point, self.player.point, alpha);
 		let mut game_count_use = 0;
-		let graphics_draw = |ctx: &mut Context, fs: u32, ds: &str, dp: [f32; 2]| {
-			let font = graphics::Font::new(ctx, "/SoberbaSerif-Regular.ttf", fs).unwrap();
-			let display = graphics::Text::new(ctx, ds, &font).unwrap();
-			let display_point = graphics::Point2::new(dp[0], dp[1]);
-			graphics::draw(ctx, &display, display_point, 0.0).unwrap();
-
-		};
 
 		let rel_u32 = |mut c: u32| -> u32 {
 			(c as f32 * RELATIVE_X) as u32
@@ -981,26 +2111,23 @@ impl ggez::event::EventHandler for MainState {
 			[c[0] * RELATIVE_X, c[1] * RELATIVE_Y]
 		};
 
+		// Pause中は止めたWindowStateを描画する
+		let shown_state = match self.window_state {
+			WindowState::Pause => self.resume_state,
+			s => s,
+		};
+
 		// match Window State
-		match self.window_state {
+		match shown_state {
 			WindowState::Title => {
-				// Print "poi-Project"
-				graphics_draw(ctx, rel_u32(100), "poi-Project", rel_poi([300.0; 2]));
-
-				// Write "-"
-				graphics::rectangle(
-					ctx,
-					graphics::DrawMode::Fill,
-					graphics::Rect::new(
-						495.0 * RELATIVE_X,
-						380.0 * RELATIVE_Y,
-						40.0 * RELATIVE_X,
-						10.0 * RELATIVE_Y
-						),
-				);
-
-				// Print "press Z key"
-				graphics_draw(ctx, rel_u32(30), "Please press Z key", rel_poi([400.0, 600.0]));
+				self.title.draw(ctx, &self.high_scores, &self.strings, &mut self.texts)?;
+				self.fx.draw_fade(ctx)?;
+				if self.options.is_open() {
+					self.options.draw(ctx, &self.strings, &mut self.texts)?;
+				}
+				if self.key_config.is_open() {
+					self.key_config.draw(ctx, &self.strings, &mut self.texts)?;
+				}
 
 				// Skip other code
 				graphics::present(ctx);
@@ -1015,11 +2142,11 @@ impl ggez::event::EventHandler for MainState {
 				// Print Boss life
 				let bs = &self.boss;
 				if self.boss.len() >= 1 {
-					let dis_str = format!("Boss: {}", bs[0].life);
-					graphics_draw(ctx, rel_u32(18), &dis_str, [bs[0].point[0] + 50.0, bs[0].point[1]]);
-					let count_down = (bs[0].moving[0].count - self.game_count[1]) / 60;
-					let dis_str = format!("Time: {}", count_down);
-					graphics_draw(ctx, rel_u32(18), &dis_str, rel_poi([700.0 ,40.0]));
+					let dis_str = self.strings.format("hud.boss", &[&bs[0].life.to_string()]);
+					self.texts.draw(ctx, &dis_str, rel_u32(18), [bs[0].point[0] + 50.0, bs[0].point[1]], Align::Left, Some(OUTLINE_COLOR))?;
+					let count_down = bs[0].moving[0].count.saturating_sub(self.game_count[1]) / 60;
+					let dis_str = self.strings.format("hud.time", &[&count_down.to_string()]);
+					self.texts.draw(ctx, &dis_str, rel_u32(18), rel_poi([810.0, 40.0]), Align::Right, Some(OUTLINE_COLOR))?;
 				} else {
 					//eprintln!("there is no boss");
 				}
@@ -1027,10 +2154,16 @@ impl ggez::event::EventHandler for MainState {
 			_ => (),
 		}
 
+		// ステージの中は揺れの分ずらす Frame, HUDはずらさない
+		self.fx.apply(ctx)?;
+
+		// 背景はFrameの下に描画する
+		self.background.draw(ctx)?;
+
 		// drow player circle
-		if !(self.player.memo == "trans".to_owned() && game_count_use % 3 == 0) {
-			Assets::draw_player(ctx, &mut self.assets, &self.player, self.input)?;
-			if self.input.shift {
+		if !(self.player.status == PlayerStatus::Trans && game_count_use % 3 == 0) {
+			self.assets.draw_player(ctx, &self.sheets, &self.player, alpha)?;
+			if self.input.shift || self.settings.show_hitbox {
 				graphics::circle(
 					ctx,
 					graphics::DrawMode::Fill,
@@ -1041,62 +2174,65 @@ impl ggez::event::EventHandler for MainState {
 			}
 		}
 
+		// 自機の弾, 敵, アイテム, 敵弾, エフェクトの順にSpriteBatchで描画する
 		// drow shot rectangle
-		for act in &mut self.plshots {
-			let point = act.point;
-			graphics::rectangle(
-				ctx,
-				graphics::DrawMode::Fill,
-				graphics::Rect::new(
-					point[0] - 8.0 * RELATIVE_X,
-					point[1] - 15.0 * RELATIVE_X,
-					16.0 * RELATIVE_X,
-					30.0 * RELATIVE_Y
-				),
-			);
+		for act in &self.plshots {
+			let point = lerp_point(act.prev_point, act.point, alpha);
+			let size = [16.0 * RELATIVE_X, 30.0 * RELATIVE_Y];
+			self.assets.sprites.add_shape(Layer::PlayerShots, Shape::Square, point, size, 0.0, graphics::WHITE);
 		}
 
 		// drow boss circle
-		for act in &mut self.boss {
-			let point = act.point;
-			graphics::circle(
-				ctx,
-				graphics::DrawMode::Fill,
-				graphics::Point2::new(point[0],point[1]),
-				30.0 * RELATIVE_X,
-				0.1,
-			);
-		}
-
-		// drow enemy circle
-		for act in &mut self.enemys {
-			let point = act.point;
-			graphics::circle(
-				ctx,
-				graphics::DrawMode::Fill,
-				graphics::Point2::new(point[0],point[1]),
-				20.0 * RELATIVE_X,
-				0.1,
-			);
-		}
-
-		// drow enshots circle
-		for act in &mut self.enshots {
-			let point = act.point;
-			graphics::circle(
-				ctx,
-				graphics::DrawMode::Fill,
-				graphics::Point2::new(point[0],point[1]),
-				10.0 * RELATIVE_X,
-				0.1,
-			);
+		for act in &self.boss {
+			let d = 60.0 * RELATIVE_X;
+			self.assets.add_actor(&self.sheets, Layer::Enemies, act, alpha, Shape::Disc, [d, d], graphics::WHITE);
+		}
+
+		// drow enemy
+		// 倒された敵はdeathのアニメーションが終わるまで描画する
+		for act in self.enemys.iter().chain(self.dying.iter()) {
+			let d = act.bbox_size * RELATIVE_X * 2.0;
+			self.assets.add_actor(&self.sheets, Layer::Enemies, act, alpha, Shape::Disc, [d, d], graphics::WHITE);
+		}
+
+		// draw item rectangle
+		for it in &self.items {
+			let color = match it.item {
+				Some(ItemKind::Power) => graphics::Color::from((255, 80, 80, 255)),
+				Some(ItemKind::Point) => graphics::Color::from((80, 120, 255, 255)),
+				Some(ItemKind::Bomb) => graphics::Color::from((80, 255, 120, 255)),
+				_ => graphics::Color::from((255, 120, 255, 255)),
+			};
+			let size = [16.0 * RELATIVE_X, 16.0 * RELATIVE_Y];
+			self.assets.add_actor(&self.sheets, Layer::Items, it, alpha, Shape::Square, size, color);
+		}
+
+		// draw enshots
+		// 弾の種類毎の画像を切り出して描画
+		let img = self.assets.bullet_img;
+		let (img_w, img_h) = {
+			let image = self.assets.sprites.image(img);
+			(image.width() as f32, image.height() as f32)
+		};
+		for act in self.enshots.iter() {
+			let point = lerp_point(act.prev_point, act.point, alpha);
+			let drawparams = graphics::DrawParam {
+				src: self.bullet_types.src_rect(act.style, img_w, img_h),
+				dest: graphics::Point2::new(point[0], point[1]),
+				rotation: self.bullet_types.rotation(act.style, act.velocity[0], act.count),
+				offset: graphics::Point2::new(0.5, 0.5),
+				scale: graphics::Point2::new(RELATIVE_X, RELATIVE_Y),
+				..Default::default()
+			};
+			let blend = self.bullet_types.get(act.style.kind).blend;
+			self.assets.sprites.add(Layer::EnemyBullets, img, blend, drawparams);
 		}
 
 		// draw effect
-		for ef in &mut self.effects {
-			match ef.memo.as_str() {
-				"bomb_wave" => {
-					let img = &self.assets.effect_img;
+		let img = self.assets.effect_img;
+		for ef in &self.effects {
+			match ef.effect {
+				Some(EffectKind::BombWave) => {
 					let img_scale = ef.count as f32 * 0.1 * RELATIVE_X;
 					let ce = 255 - ef.count as u8;
 					let img_color = graphics::Color::from((ce, ce, ce, 255));
@@ -1108,12 +2244,41 @@ impl ggez::event::EventHandler for MainState {
 						color: Some(img_color),
 						..Default::default()
 					};
-					graphics::draw_ex(ctx, img, drawparams);
+					self.assets.sprites.add(Layer::Effects, img, Blend::Alpha, drawparams);
+				},
+				Some(EffectKind::Burst) => {
+					let img_scale = (0.5 + ef.count as f32 * 0.05) * 0.1 * RELATIVE_X;
+					let ca = 255 - (ef.count * 8).min(255) as u8;
+					let img_color = graphics::Color::from((255, 255, 255, ca));
+
+					let drawparams = graphics::DrawParam {
+						dest: graphics::Point2::new(ef.point[0], ef.point[1]),
+						offset: graphics::Point2::new(0.482, 0.5),
+						scale: graphics::Point2::new(img_scale, img_scale),
+						color: Some(img_color),
+						..Default::default()
+					};
+					self.assets.sprites.add(Layer::Effects, img, Blend::Alpha, drawparams);
 				},
 				_ => (),
 			}
 		}
 
+		self.particles.add_to(&mut self.assets.sprites, alpha);
+
+		let sprites = &mut self.assets.sprites;
+		sprites.draw_layer(ctx, Layer::PlayerShots)?;
+		sprites.draw_layer(ctx, Layer::Enemies)?;
+		sprites.draw_layer(ctx, Layer::Items)?;
+		// レーザーは敵弾の下
+		for ls in &self.lasers {
+			ls.draw(ctx)?;
+		}
+		sprites.draw_layer(ctx, Layer::EnemyBullets)?;
+		sprites.draw_layer(ctx, Layer::Effects)?;
+		self.fx.draw_flash(ctx)?;
+		self.fx.restore(ctx)?;
+
 		// draw frame
 		let drawable = &self.assets.frame_img;
 		let params = graphics::DrawParam {
@@ -1123,22 +2288,47 @@ impl ggez::event::EventHandler for MainState {
 		graphics::draw_ex(ctx, drawable, params);
 
 		// Print score
-		let dis_str = format!("Score: {}", self.score);
-		graphics_draw(ctx, rel_u32(18), &dis_str, rel_poi([900.0, 100.0]));
+		let dis_str = self.strings.format("hud.score", &[&self.score.to_string()]);
+		self.texts.draw(ctx, &dis_str, rel_u32(18), rel_poi([900.0, 100.0]), Align::Left, None)?;
 
 		// Print Player life
-		let dis_str = format!("Life: {}", self.player.life as usize);
-		graphics_draw(ctx, rel_u32(18), &dis_str, rel_poi([900.0, 150.0]));
+		let dis_str = self.strings.format("hud.life", &[&(self.player.life as usize).to_string()]);
+		self.texts.draw(ctx, &dis_str, rel_u32(18), rel_poi([900.0, 150.0]), Align::Left, None)?;
 
 		// Print bomb
-		let dis_str = format!("Bomb: {}", self.bomb as usize);
-		graphics_draw(ctx, rel_u32(18), &dis_str, rel_poi([900.0, 200.0]));
+		let dis_str = self.strings.format("hud.bomb", &[&(self.bomb as usize).to_string()]);
+		self.texts.draw(ctx, &dis_str, rel_u32(18), rel_poi([900.0, 200.0]), Align::Left, None)?;
+
+		// Print power
+		let dis_str = self.strings.format("hud.power", &[&self.power.to_string()]);
+		self.texts.draw(ctx, &dis_str, rel_u32(18), rel_poi([900.0, 250.0]), Align::Left, None)?;
+
+		// Print difficulty, Practice, Replay
+		let difficulty = self.strings.get(self.difficulty.key());
+		let dis_str = if self.playback.is_some() {
+			self.strings.format("hud.replay", &[difficulty])
+		} else if let Some(ref p) = self.practice {
+			self.strings.format("hud.practice", &[difficulty, &p.section])
+		} else if let Some(ref sp) = self.spell {
+			let [captures, attempts] = self.high_scores.spell(sp, self.difficulty);
+			self.strings.format("hud.spell", &[difficulty, &captures.to_string(), &attempts.to_string()])
+		} else {
+			difficulty.to_owned()
+		};
+		self.texts.draw(ctx, &dis_str, rel_u32(18), rel_poi([900.0, 50.0]), Align::Left, None)?;
+
+		// Print FPS, update time, step policy
+		if self.show_overlay {
+			let dis_str = self.scheduler.overlay_text();
+			self.texts.draw(ctx, &dis_str, rel_u32(14), rel_poi([860.0, 920.0]), Align::Left, Some(OUTLINE_COLOR))?;
+		}
 
 		match self.window_state {
 			WindowState::GameOver => {
 				let drawable = &self.assets.brack_out_img;
 				let params = graphics::DrawParam {
 					scale: graphics::Point2::new(RELATIVE_X, RELATIVE_Y),
+					color: Some(self.fx.overlay_color(self.game_count[0], FADE_FRAMES)),
 					..Default::default()
 				};
 				graphics::draw_ex(ctx, drawable, params);
@@ -1153,9 +2343,11 @@ impl ggez::event::EventHandler for MainState {
 				graphics::set_color(ctx, graphics::Color::from((c, c, c, 255)))?;
 				// --------------------
 
-				graphics_draw(ctx, rel_u32(30), "GameOver", rel_poi([400.0, 300.0]));
+				let center = (STAGE_LEFT + STAGE_RIGHT) / 2.0;
+				self.texts.draw(ctx, self.strings.get("screen.game_over"), rel_u32(30), [center, 300.0 * RELATIVE_Y], Align::Center, None)?;
 				if self.game_count[0] >= 240 {
-					graphics_draw(ctx, rel_u32(20), "Press Z to close winodw", rel_poi([350.0, 400.0]));
+					let close = self.close_window_text();
+					self.texts.draw(ctx, &close, rel_u32(20), [center, 400.0 * RELATIVE_Y], Align::Center, None)?;
 				}
 
 				graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))?;
@@ -1164,10 +2356,23 @@ impl ggez::event::EventHandler for MainState {
 				let drawable = &self.assets.brack_out_img;
 				let params = graphics::DrawParam {
 					scale: graphics::Point2::new(RELATIVE_X, RELATIVE_Y),
+					color: Some(self.fx.overlay_color(self.game_count[0], FADE_FRAMES)),
 					..Default::default()
 				};
 				graphics::draw_ex(ctx, drawable, params);
-				graphics_draw(ctx, rel_u32(30), "GameClear", rel_poi([400.0, 300.0]));
+				let center = (STAGE_LEFT + STAGE_RIGHT) / 2.0;
+				self.texts.draw(ctx, self.strings.get("screen.game_clear"), rel_u32(30), [center, 300.0 * RELATIVE_Y], Align::Center, None)?;
+			},
+			WindowState::Dialogue => {
+				if let Some(ref mut d) = self.dialogue {
+					d.draw(ctx, &mut self.texts)?;
+				}
+			},
+			WindowState::Pause => {
+				if let Some(ref mut d) = self.dialogue {
+					d.draw(ctx, &mut self.texts)?;
+				}
+				self.pause.draw(ctx, &self.strings, &mut self.texts)?;
 			},
 			WindowState::ThankYouForPlaying => {
 				// ゆっくり明るくなっていく
@@ -1189,47 +2394,88 @@ impl ggez::event::EventHandler for MainState {
 				graphics::draw_ex(ctx, drawable, params);
 
 				// draw text
-				graphics_draw(ctx, rel_u32(60), "Thank you", rel_poi([700.0, 200.0]));
-				graphics_draw(ctx, rel_u32(60), "for playing", rel_poi([750.0, 350.0]));
+				self.texts.draw(ctx, self.strings.get("screen.thank_you"), rel_u32(60), rel_poi([700.0, 200.0]), Align::Left, None)?;
+				self.texts.draw(ctx, self.strings.get("screen.for_playing"), rel_u32(60), rel_poi([750.0, 350.0]), Align::Left, None)?;
 
 				if self.game_count[0] >= 240 {
-					graphics_draw(ctx, rel_u32(30), "Press Z to close winodw", rel_poi([700.0, 550.0]));
+					let close = self.close_window_text();
+					self.texts.draw(ctx, &close, rel_u32(30), rel_poi([700.0, 550.0]), Align::Left, None)?;
 				}
 			}
 			_ => (),
 		}
 
+		self.fx.draw_fade(ctx)?;
+
+		if self.options.is_open() {
+			self.options.draw(ctx, &self.strings, &mut self.texts)?;
+		}
+		if self.key_config.is_open() {
+			self.key_config.draw(ctx, &self.strings, &mut self.texts)?;
+		}
+
 		graphics::present(ctx);
 		Ok(())
 
 	}
 
-	fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod, _repeat: bool) {
-		match keycode {
-			Keycode::Up => self.input.up = true,
-			Keycode::Down => self.input.down = true,
-			Keycode::Right => self.input.right = true,
-			Keycode::Left => self.input.left = true,
-			Keycode::LShift => self.input.shift = true,
-			Keycode::Z => self.input.shot = true,
-			Keycode::X => self.input.bomb = true,
-			_ => ()
+	fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod, repeat: bool) {
+		if self.key_config.bind_key(keycode, &mut self.controls.bindings) {
+			self.save_settings();
+			return;
 		}
+		let action = self.controls.key_down(keycode);
+		if self.playback.is_none() {
+			match keycode {
+				Keycode::F3 => self.show_overlay = !self.show_overlay,
+				Keycode::F4 => self.scheduler.policy = self.scheduler.policy.next(),
+				_ => (),
+			}
+		}
+		// 押しっぱなしの繰り返しはメニューのカーソル移動だけ
+		if let Some(action) = action {
+			let is_move = [Action::Up, Action::Down, Action::Left, Action::Right].contains(&action);
+			if !repeat || is_move {
+				self.action_down(ctx, action);
+			}
+		}
+		self.update_input();
 	}
 
-	fn key_up_event(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod, _repeat: bool) {
-		match keycode {
-			Keycode::Up => self.input.up = false,
-			Keycode::Down => self.input.down = false,
-			Keycode::Right => self.input.right = false,
-			Keycode::Left => self.input.left = false,
-			Keycode::LShift => self.input.shift = false,
-			Keycode::Z => {
-				self.input.shot = false;
-				self.input_break.shot = false;
-			}
-			Keycode::X => self.input.bomb = false,
-			_ => ()
+	fn key_up_event(&mut self, _ctx: &mut Context, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+		self.controls.key_up(keycode);
+		self.update_input();
+	}
+
+	fn controller_button_down_event(&mut self, ctx: &mut Context, btn: Button, _instance_id: i32) {
+		if self.key_config.bind_button(btn, &mut self.controls.bindings) {
+			self.save_settings();
+			return;
+		}
+		if let Some(action) = self.controls.button_down(btn) {
+			self.action_down(ctx, action);
+		}
+		self.update_input();
+	}
+
+	fn controller_button_up_event(&mut self, _ctx: &mut Context, btn: Button, _instance_id: i32) {
+		self.controls.button_up(btn);
+		self.update_input();
+	}
+
+	fn controller_axis_event(&mut self, ctx: &mut Context, axis: Axis, value: i16, _instance_id: i32) {
+		if let Some(action) = self.controls.axis(axis, value) {
+			self.action_down(ctx, action);
+		}
+		self.update_input();
+	}
+
+	fn focus_event(&mut self, _ctx: &mut Context, gained: bool) {
+		// フォーカスが外れている間のkey_upは届かないので入力を戻す
+		if !gained {
+			self.pause_game();
+			self.controls.clear();
+			self.input = InputState::new();
 		}
 	}
 }
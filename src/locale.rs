@@ -0,0 +1,89 @@
+use ggez::GameResult;
+use ggez::error::GameError;
+
+use serde_json;
+
+use std::collections::BTreeMap;
+
+use load_json;
+use settings::Language;
+
+// 画面に出す文字列の翻訳
+// LANG_DIR/{言語のcode}.json に key -> 文字列 を書く
+//
+// 選んだ言語に無いkeyは英語を使い, 英語にも無ければkeyをそのまま出す
+// （リプレイのファイル名などkeyでない文字列はそのまま表示される）
+// "{}"はformatの引数で順に置き換える
+
+pub const LANG_DIR: &str = "resources/lang";
+
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+	strings: BTreeMap<String, String>,
+}
+
+impl Catalog {
+	pub fn path(language: Language) -> String {
+		format!("{}/{}.json", LANG_DIR, language.code())
+	}
+
+	pub fn load(path: &str) -> GameResult<Catalog> {
+		let strings = serde_json::from_value(load_json(path)?)
+			.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", path, e)))?;
+		Ok(Catalog { strings })
+	}
+
+	// keyの順
+	pub fn keys(&self) -> Vec<&str> {
+		self.strings.keys().map(|k| k.as_str()).collect()
+	}
+
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.strings.get(key).map(|s| s.as_str())
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Strings {
+	language: Language,
+	catalog: Catalog,
+	fallback: Catalog,
+	// 英語
+}
+
+impl Strings {
+	pub fn load(language: Language) -> GameResult<Strings> {
+		let fallback = Catalog::load(&Catalog::path(Language::English))?;
+		let catalog = if language == Language::English {
+			fallback.clone()
+		} else {
+			Catalog::load(&Catalog::path(language))?
+		};
+		Ok(Strings { language, catalog, fallback })
+	}
+
+	pub fn language(&self) -> Language {
+		self.language
+	}
+
+	pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+		self.catalog.get(key)
+			.or_else(|| self.fallback.get(key))
+			.unwrap_or(key)
+	}
+
+	pub fn format(&self, key: &str, args: &[&str]) -> String {
+		let mut s = self.get(key).to_owned();
+		let mut from = 0;
+		for a in args {
+			match s[from..].find("{}") {
+				Some(i) => {
+					s.replace_range(from + i..from + i + 2, a);
+					from += i + a.len();
+				},
+				None => break,
+			}
+		}
+		s
+	}
+}
@@ -8,10 +8,53 @@ use ggez::conf;
 use std::{path, env};
 
 use poi_project::MainState;
+use poi_project::mode::Difficulty;
 use poi_project::SCREEN_WIDTH;
 use poi_project::SCREEN_HEIGHT;
 
+// コマンドライン引数
+// --spell <Boss名:phase>  Spell practiceで1つのphaseを繰り返す (例: boss:b_6rotate_4rand, m_boss:2)
+// --difficulty <難易度>   easy, normal, hard, lunatic（省略時normal）
+// --no-audio             BGM, 効果音を鳴らさない
+// --no-particles         パーティクルを出さない
+struct Args {
+	spell: Option<String>,
+	difficulty: Difficulty,
+	no_audio: bool,
+	no_particles: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+	let mut args = Args { spell: None, difficulty: Difficulty::default(), no_audio: false, no_particles: false };
+	let mut it = env::args().skip(1);
+	while let Some(a) = it.next() {
+		match a.as_str() {
+			"--spell" => {
+				args.spell = Some(it.next().ok_or("--spell needs <boss:phase>")?);
+			},
+			"--difficulty" => {
+				let name = it.next().ok_or("--difficulty needs a name")?;
+				args.difficulty = Difficulty::from_name(&name)
+					.ok_or_else(|| format!("unknown difficulty \"{}\"", name))?;
+			},
+			"--no-audio" => args.no_audio = true,
+			"--no-particles" => args.no_particles = true,
+			_ => return Err(format!("unknown argument \"{}\"", a)),
+		}
+	}
+	Ok(args)
+}
+
 pub fn main() {
+	let args = match parse_args() {
+		Ok(args) => args,
+		Err(e) => {
+			println!("Error: {}", e);
+			println!("Usage: poi-project [--spell <boss:phase>] [--difficulty <easy|normal|hard|lunatic>] [--no-audio] [--no-particles]");
+			return;
+		}
+	};
+
 	let w = SCREEN_WIDTH as u32;
 	let h = SCREEN_HEIGHT as u32;
 	let mut cb = ContextBuilder::new("poi-project", "ggez")
@@ -42,6 +85,18 @@ pub fn main() {
 			println!("Error: {}", e);
 		}
 		Ok(ref mut game) => {
+			if args.no_audio {
+				game.disable_audio();
+			}
+			if args.no_particles {
+				game.disable_particles();
+			}
+			if let Some(ref spell) = args.spell {
+				if let Err(e) = game.practice_spell(spell, args.difficulty) {
+					println!("Error: {}", e);
+					return;
+				}
+			}
 			let result = event::run(ctx, game);
 			if let Err(e) = result {
 				println!("Error encountered running game: {}", e);
@@ -0,0 +1,126 @@
+use ggez::graphics;
+use ggez::{Context, GameResult};
+
+use bindings::Action;
+use fonts::{Align, TextCache};
+use locale::Strings;
+
+// 縦に並んだ項目から1つを選ぶメニュー
+// 上下でカーソルを動かす（端まで行くと反対側へ戻る）
+// Shot: 決定  Bomb, Pause: 戻る（キー設定はbindings）
+//
+// Title, Pauseなどの画面はページ毎にMenuを持ち,
+// MenuEventを見て次のページへ進む, 前のページへ戻る
+//
+// 項目の名前はlocaleのkey（keyでない文字列はそのまま表示する）
+// 値は表示する文字列のまま持つ "名前: 値"
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuEvent {
+	None,
+	Confirm(usize),
+	// 選んだ項目の番号
+	Cancel,
+}
+
+#[derive(Debug, Clone)]
+struct Item {
+	label: String,
+	value: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Menu {
+	items: Vec<Item>,
+	cursor: usize,
+}
+
+fn items(labels: Vec<String>) -> Vec<Item> {
+	labels.into_iter().map(|label| Item { label, value: None }).collect()
+}
+
+impl Menu {
+	pub fn new(items: &[&str]) -> Menu {
+		Menu {
+			items: self::items(items.iter().map(|s| s.to_string()).collect()),
+			cursor: 0,
+		}
+	}
+
+	pub fn cursor(&self) -> usize {
+		self.cursor
+	}
+
+	pub fn len(&self) -> usize {
+		self.items.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.items.is_empty()
+	}
+
+	pub fn reset(&mut self) {
+		self.cursor = 0;
+	}
+
+	pub fn set_cursor(&mut self, i: usize) {
+		if i < self.items.len() {
+			self.cursor = i;
+		}
+	}
+
+	pub fn up(&mut self) {
+		let n = self.items.len();
+		if n > 0 {
+			self.cursor = (self.cursor + n - 1) % n;
+		}
+	}
+
+	pub fn down(&mut self) {
+		let n = self.items.len();
+		if n > 0 {
+			self.cursor = (self.cursor + 1) % n;
+		}
+	}
+
+	// 項目の値を書き換える（設定の値など）
+	pub fn set_value(&mut self, i: usize, value: String) {
+		self.items[i].value = Some(value);
+	}
+
+	// 項目を全て入れ替える（リプレイの一覧など）
+	pub fn set_items(&mut self, labels: Vec<String>) {
+		self.items = items(labels);
+		self.cursor = 0;
+	}
+
+	pub fn input(&mut self, action: Action) -> MenuEvent {
+		match action {
+			Action::Up => self.up(),
+			Action::Down => self.down(),
+			Action::Shot if !self.items.is_empty() => return MenuEvent::Confirm(self.cursor),
+			Action::Bomb | Action::Pause => return MenuEvent::Cancel,
+			_ => (),
+		}
+		MenuEvent::None
+	}
+
+	// point: 1行目の左上 (Windowの座標)
+	pub fn draw(&self, ctx: &mut Context, strings: &Strings, texts: &mut TextCache, point: [f32; 2], size: u32, line_height: f32) -> GameResult<()> {
+		for (i, item) in self.items.iter().enumerate() {
+			let label = strings.get(&item.label);
+			let s = match item.value {
+				Some(ref v) => format!("{}: {}", label, v),
+				None => label.to_owned(),
+			};
+			let (s, color) = if i == self.cursor {
+				(format!("> {}", s), graphics::Color::from((255, 230, 80, 255)))
+			} else {
+				(format!("  {}", s), graphics::Color::from((200, 200, 200, 255)))
+			};
+			graphics::set_color(ctx, color)?;
+			texts.draw(ctx, &s, size, [point[0], point[1] + line_height * i as f32], Align::Left, None)?;
+		}
+		graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))
+	}
+}
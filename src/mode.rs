@@ -0,0 +1,132 @@
+// 難易度と自機の種類
+// Titleのメニューで選び, リプレイ, スコアにも記録する
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+	Easy,
+	Normal,
+	Hard,
+	Lunatic,
+}
+
+impl Difficulty {
+	pub const ALL: [Difficulty; 4] = [
+		Difficulty::Easy,
+		Difficulty::Normal,
+		Difficulty::Hard,
+		Difficulty::Lunatic,
+	];
+
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Difficulty::Easy => "Easy",
+			Difficulty::Normal => "Normal",
+			Difficulty::Hard => "Hard",
+			Difficulty::Lunatic => "Lunatic",
+		}
+	}
+
+	// localeのkey
+	pub fn key(&self) -> &'static str {
+		match *self {
+			Difficulty::Easy => "difficulty.easy",
+			Difficulty::Normal => "difficulty.normal",
+			Difficulty::Hard => "difficulty.hard",
+			Difficulty::Lunatic => "difficulty.lunatic",
+		}
+	}
+
+	// 大文字, 小文字は区別しない
+	pub fn from_name(name: &str) -> Option<Difficulty> {
+		Difficulty::ALL.iter().cloned().find(|d| d.name().eq_ignore_ascii_case(name))
+	}
+
+	pub fn lives(&self) -> f32 {
+		match *self {
+			Difficulty::Easy => 5.0,
+			Difficulty::Normal | Difficulty::Hard => 3.0,
+			Difficulty::Lunatic => 2.0,
+		}
+	}
+
+	pub fn bombs(&self) -> u32 {
+		match *self {
+			Difficulty::Easy | Difficulty::Normal => 4,
+			Difficulty::Hard => 3,
+			Difficulty::Lunatic => 2,
+		}
+	}
+
+	// 敵弾の速さの倍率
+	pub fn bullet_speed(&self) -> f32 {
+		match *self {
+			Difficulty::Easy => 0.7,
+			Difficulty::Normal => 1.0,
+			Difficulty::Hard => 1.15,
+			Difficulty::Lunatic => 1.3,
+		}
+	}
+}
+
+impl Default for Difficulty {
+	fn default() -> Difficulty {
+		Difficulty::Normal
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Character {
+	Wide,
+	// 速い
+	Focus,
+	// 遅い 細かく避けやすい
+}
+
+impl Character {
+	pub const ALL: [Character; 2] = [Character::Wide, Character::Focus];
+
+	// localeのkey
+	pub fn key(&self) -> &'static str {
+		match *self {
+			Character::Wide => "character.wide",
+			Character::Focus => "character.focus",
+		}
+	}
+
+	// [高速移動, 低速Shift移動] 1秒の移動距離
+	pub fn speed(&self) -> [f32; 2] {
+		match *self {
+			Character::Wide => [350.0, 150.0],
+			Character::Focus => [300.0, 120.0],
+		}
+	}
+}
+
+impl Default for Character {
+	fn default() -> Character {
+		Character::Wide
+	}
+}
+
+// Practiceの開始条件
+// story.jsonのsectionのcountから始める
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PracticeStart {
+	pub section: String,
+	// sectionの名前
+	pub count: u32,
+	// 始めるcount
+	pub lives: u32,
+	pub bombs: u32,
+}
+
+// Spell practiceで練習するBossのphase
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpellStart {
+	pub boss: String,
+	// boss.jsonのBossの名前
+	pub phase: usize,
+	// phasesの番号
+}
@@ -0,0 +1,144 @@
+use ggez::graphics;
+use ggez::{Context, GameResult};
+
+use bindings::Action;
+use fonts::{Align, TextCache};
+use locale::Strings;
+use menu::{Menu, MenuEvent};
+use scheduler::StepPolicy;
+use settings::{step_volume, Settings};
+use {RELATIVE_X, RELATIVE_Y, SCREEN_WIDTH, SCREEN_HEIGHT};
+
+// Options画面
+// TitleとPauseから開き, Title, Pauseの上に重ねて描画する
+// 左右で値を変える（Shotでも次の値へ進む）
+// Settingsの項目は変えるとすぐ保存する FPS display, Frame stepは保存しない
+
+const ITEMS: [&str; 13] = [
+	"options.window_scale",
+	"options.fullscreen",
+	"options.vsync",
+	"options.bgm_volume",
+	"options.se_volume",
+	"options.difficulty",
+	"options.show_hitbox",
+	"options.screen_effects",
+	"options.language",
+	"options.fps_display",
+	"options.frame_step",
+	"options.key_config",
+	"options.back",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptionsAction {
+	None,
+	Changed,
+	// Settingsを変えた 保存して反映する
+	ToggleOverlay,
+	NextPolicy,
+	KeyConfig,
+	Close,
+}
+
+#[derive(Debug, Clone)]
+pub struct OptionsScreen {
+	menu: Menu,
+	open: bool,
+}
+
+impl OptionsScreen {
+	pub fn new() -> OptionsScreen {
+		OptionsScreen {
+			menu: Menu::new(&ITEMS),
+			open: false,
+		}
+	}
+
+	pub fn open(&mut self) {
+		self.menu.reset();
+		self.open = true;
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.open
+	}
+
+	// 項目に今の値を表示する
+	// 言語の名前はその言語で表示する
+	pub fn set_values(&mut self, settings: &Settings, overlay: bool, policy: StepPolicy, strings: &Strings) {
+		let on_off = |b: bool| strings.get(if b { "options.on" } else { "options.off" }).to_owned();
+		let values = [
+			format!("x{}", settings.window_scale),
+			on_off(settings.fullscreen),
+			on_off(settings.vsync),
+			format!("{}", settings.bgm_volume),
+			format!("{}", settings.se_volume),
+			strings.get(settings.difficulty.key()).to_owned(),
+			on_off(settings.show_hitbox),
+			if settings.screen_effects == 0 {
+				strings.get("options.off").to_owned()
+			} else {
+				format!("{}%", settings.screen_effects)
+			},
+			settings.language.name().to_owned(),
+			on_off(overlay),
+			policy.name(),
+		];
+		for (i, v) in values.iter().enumerate() {
+			self.menu.set_value(i, format!("< {} >", v));
+		}
+	}
+
+	// i番目の項目の値を変える
+	fn change(&self, i: usize, dir: i32, settings: &mut Settings) -> OptionsAction {
+		match i {
+			0 => settings.step_scale(dir),
+			1 => settings.fullscreen = !settings.fullscreen,
+			2 => settings.vsync = !settings.vsync,
+			3 => settings.bgm_volume = step_volume(settings.bgm_volume, dir),
+			4 => settings.se_volume = step_volume(settings.se_volume, dir),
+			5 => settings.step_difficulty(dir),
+			6 => settings.show_hitbox = !settings.show_hitbox,
+			7 => settings.step_screen_effects(dir),
+			8 => settings.step_language(dir),
+			9 => return OptionsAction::ToggleOverlay,
+			10 => return OptionsAction::NextPolicy,
+			_ => return OptionsAction::None,
+		}
+		OptionsAction::Changed
+	}
+
+	pub fn input(&mut self, action: Action, settings: &mut Settings) -> OptionsAction {
+		let i = self.menu.cursor();
+		match action {
+			Action::Left => return self.change(i, -1, settings),
+			Action::Right => return self.change(i, 1, settings),
+			_ => (),
+		}
+		match self.menu.input(action) {
+			MenuEvent::None => OptionsAction::None,
+			MenuEvent::Confirm(11) => OptionsAction::KeyConfig,
+			MenuEvent::Confirm(i) if i < 11 => self.change(i, 1, settings),
+			MenuEvent::Confirm(_) | MenuEvent::Cancel => {
+				self.open = false;
+				OptionsAction::Close
+			},
+		}
+	}
+
+	pub fn draw(&self, ctx: &mut Context, strings: &Strings, texts: &mut TextCache) -> GameResult<()> {
+		graphics::set_color(ctx, graphics::Color::from((0, 0, 0, 220)))?;
+		graphics::rectangle(
+			ctx,
+			graphics::DrawMode::Fill,
+			graphics::Rect::new(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT),
+		)?;
+		graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))?;
+
+		texts.draw(ctx, strings.get("options.heading"), (40.0 * RELATIVE_X) as u32, [200.0 * RELATIVE_X, 120.0 * RELATIVE_Y], Align::Left, None)?;
+
+		let size = (24.0 * RELATIVE_X) as u32;
+		self.menu.draw(ctx, strings, texts, [200.0 * RELATIVE_X, 220.0 * RELATIVE_Y], size, 50.0 * RELATIVE_Y)
+	}
+}
@@ -0,0 +1,211 @@
+use ggez::graphics;
+use ggez::GameResult;
+use ggez::error::GameError;
+
+use serde_json;
+
+use rand::{Rng, SeedableRng};
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use sprite::{shape_param, Blend, Layer, Shape, SpriteRenderer};
+use {lerp_point, load_json, GameRng, RELATIVE_X, RELATIVE_Y};
+
+// particles.json の読み込みとパーティクルの更新, 描画
+// emitter: 1回に出すパーティクルの数, 寿命, 速さ, 向き, 寿命の間の色と大きさ
+//
+// パーティクルは見た目だけでゲームに影響しない
+// 乱数はゲームの乱数と別なので, 出さなくても（disable）リプレイは同じになる
+
+const MAX_PARTICLES: usize = 2000;
+// これより多い時は新しく出さない
+
+fn default_spread() -> f32 {
+	2.0
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct EmitterData {
+	count: u32,
+	// 1回に出す数
+	lifetime: [u32; 2],
+	// 寿命count [最小, 最大]
+	speed: [f32; 2],
+	// 速さ (px/s) [最小, 最大]
+	#[serde(default)]
+	direction: f32,
+	// 向き 弾と同じ角度（0が下, 0.5が右, 単位π）
+	#[serde(default = "default_spread")]
+	spread: f32,
+	// directionを中心に広がる角度（単位π） 2で全方向
+	#[serde(default)]
+	gravity: f32,
+	// 下向きの加速度 (px/s^2)
+	colors: Vec<[u8; 4]>,
+	// 寿命の間に順に変わる色 [r, g, b, a]
+	sizes: Vec<f32>,
+	// 寿命の間に順に変わる直径 (px)
+	#[serde(default)]
+	blend: Blend,
+}
+
+// 長さlenのlistを寿命に等間隔に並べた時, 寿命の割合t (0.0 ~ 1.0) で補間する2つの番号と割合
+fn over_life(len: usize, t: f32) -> (usize, usize, f32) {
+	let last = len - 1;
+	let pos = t.min(1.0) * last as f32;
+	let i = (pos as usize).min(last);
+	(i, (i + 1).min(last), pos - i as f32)
+}
+
+impl EmitterData {
+	fn color(&self, t: f32) -> graphics::Color {
+		let (i, j, r) = over_life(self.colors.len(), t);
+		let (a, b) = (self.colors[i], self.colors[j]);
+		let c = |k: usize| (a[k] as f32 + (b[k] as f32 - a[k] as f32) * r) as u8;
+		graphics::Color::from((c(0), c(1), c(2), c(3)))
+	}
+
+	fn size(&self, t: f32) -> f32 {
+		let (i, j, r) = over_life(self.sizes.len(), t);
+		self.sizes[i] + (self.sizes[j] - self.sizes[i]) * r
+	}
+}
+
+pub fn load(path: &str) -> GameResult<HashMap<String, EmitterData>> {
+	let v = load_json(path)?;
+	let emitters: HashMap<String, EmitterData> = serde_json::from_value(v["emitter"].to_owned())
+		.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", path, e)))?;
+
+	for (name, e) in &emitters {
+		let err = |m: &str| Err(GameError::ResourceLoadError(format!("{}: emitter \"{}\": {}", path, name, m)));
+		if e.colors.is_empty() {
+			return err("colors is empty");
+		}
+		if e.sizes.is_empty() {
+			return err("sizes is empty");
+		}
+		if e.lifetime[0] == 0 || e.lifetime[0] > e.lifetime[1] {
+			return err("lifetime must be [min, max] with 0 < min <= max");
+		}
+		if e.speed[0] > e.speed[1] {
+			return err("speed must be [min, max]");
+		}
+	}
+	Ok(emitters)
+}
+
+#[derive(Debug, Clone)]
+struct Particle {
+	emitter: usize,
+	point: [f32; 2],
+	prev_point: [f32; 2],
+	velocity: [f32; 2],
+	// [x, y] (px/s)
+	count: u32,
+	lifetime: u32,
+}
+
+#[derive(Debug)]
+pub struct Particles {
+	emitters: Vec<EmitterData>,
+	names: HashMap<String, usize>,
+	// emitterの名前 -> emittersの番号
+	particles: Vec<Particle>,
+	rand: GameRng,
+	enabled: bool,
+	// falseなら何も出さない（画面の無い実行など）
+}
+
+impl Particles {
+	pub fn new(emitters: HashMap<String, EmitterData>) -> Particles {
+		let mut names = HashMap::new();
+		let mut list = Vec::new();
+		for (name, e) in emitters {
+			names.insert(name, list.len());
+			list.push(e);
+		}
+		Particles {
+			emitters: list,
+			names,
+			particles: Vec::with_capacity(MAX_PARTICLES),
+			rand: GameRng::seed_from_u64(0),
+			enabled: true,
+		}
+	}
+
+	// 使うemitterが全てあるか
+	pub fn require(&self, names: &[&str]) -> GameResult<()> {
+		match names.iter().find(|n| !self.names.contains_key(**n)) {
+			Some(n) => Err(GameError::ResourceLoadError(
+				format!("particles.json: emitter \"{}\" is not defined", n)
+			)),
+			None => Ok(()),
+		}
+	}
+
+	pub fn set_enabled(&mut self, enabled: bool) {
+		self.enabled = enabled;
+		if !enabled {
+			self.particles.clear();
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.particles.clear();
+	}
+
+	// pointからemitterの数だけ出す
+	pub fn emit(&mut self, name: &str, point: [f32; 2]) {
+		if !self.enabled {
+			return;
+		}
+		let id = match self.names.get(name) {
+			Some(id) => *id,
+			None => return,
+		};
+		let e = &self.emitters[id];
+		for _ in 0..e.count {
+			if self.particles.len() >= MAX_PARTICLES {
+				break;
+			}
+			let angle = (e.direction + (self.rand.gen::<f32>() - 0.5) * e.spread) * PI;
+			let speed = e.speed[0] + (e.speed[1] - e.speed[0]) * self.rand.gen::<f32>();
+			let lifetime = self.rand.gen_range(e.lifetime[0], e.lifetime[1] + 1);
+			self.particles.push(Particle {
+				emitter: id,
+				point,
+				prev_point: point,
+				velocity: [speed * angle.sin(), speed * angle.cos()],
+				count: 0,
+				lifetime,
+			});
+		}
+	}
+
+	// 1count分動かし, 寿命の尽きたものを消す
+	pub fn update(&mut self, seconds: f32) {
+		let emitters = &self.emitters;
+		for p in &mut self.particles {
+			p.prev_point = p.point;
+			p.velocity[1] += emitters[p.emitter].gravity * seconds;
+			p.point[0] += p.velocity[0] * RELATIVE_X * seconds;
+			p.point[1] += p.velocity[1] * RELATIVE_Y * seconds;
+			p.count += 1;
+		}
+		self.particles.retain(|p| p.count < p.lifetime);
+	}
+
+	// Effectsの層に予約する
+	pub fn add_to(&self, sprites: &mut SpriteRenderer, alpha: f32) {
+		let shapes = sprites.shapes();
+		for p in &self.particles {
+			let e = &self.emitters[p.emitter];
+			let t = p.count as f32 / p.lifetime as f32;
+			let point = lerp_point(p.prev_point, p.point, alpha);
+			let d = e.size(t);
+			let param = shape_param(Shape::Disc, point, [d * RELATIVE_X, d * RELATIVE_Y], 0.0, e.color(t));
+			sprites.add(Layer::Effects, shapes, e.blend, param);
+		}
+	}
+}
@@ -0,0 +1,221 @@
+use ggez::GameResult;
+use ggez::error::GameError;
+
+use Actor;
+
+// Enemyの経路移動
+// story.json の "path" で指定する
+//
+// move_to: 開始位置から points[0] まで移動
+// bezier: 開始位置を始点, pointsを制御点（最後が終点）とするベジェ曲線
+// catmull_rom: 開始位置とpointsを順に通る曲線
+//
+// 経路の途中は velocity, accel を無視して位置を直接決める
+// 経路が終わると最後の速度のまま通常の移動に戻る
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PathKind {
+	MoveTo,
+	Bezier,
+	CatmullRom,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+	Linear,
+	InQuad,
+	OutQuad,
+	InOutQuad,
+	InCubic,
+	OutCubic,
+	InOutCubic,
+	InSine,
+	OutSine,
+	InOutSine,
+}
+
+impl Default for Easing {
+	fn default() -> Easing {
+		Easing::Linear
+	}
+}
+
+impl Easing {
+	// 0.0 <= t <= 1.0
+	pub fn apply(self, t: f32) -> f32 {
+		use std::f32::consts::PI;
+		match self {
+			Easing::Linear => t,
+			Easing::InQuad => t * t,
+			Easing::OutQuad => t * (2.0 - t),
+			Easing::InOutQuad => {
+				if t < 0.5 {
+					2.0 * t * t
+				} else {
+					-1.0 + (4.0 - 2.0 * t) * t
+				}
+			},
+			Easing::InCubic => t * t * t,
+			Easing::OutCubic => {
+				let u = t - 1.0;
+				u * u * u + 1.0
+			},
+			Easing::InOutCubic => {
+				if t < 0.5 {
+					4.0 * t * t * t
+				} else {
+					let u = 2.0 * t - 2.0;
+					0.5 * u * u * u + 1.0
+				}
+			},
+			Easing::InSine => 1.0 - (t * PI / 2.0).cos(),
+			Easing::OutSine => (t * PI / 2.0).sin(),
+			Easing::InOutSine => 0.5 * (1.0 - (t * PI).cos()),
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PathElement {
+	pub count: u32,
+	// 経路を始めるcount
+	// 読み込み後はStage.countを加算した絶対値
+	pub duration: u32,
+	// 経路にかけるcount
+	pub kind: PathKind,
+	// 経路の種類
+	pub points: Vec<[f32; 2]>,
+	// 通る点 [x, y] （開始位置は含まない）
+	#[serde(default)]
+	pub easing: Easing,
+	// 進み方
+	#[serde(default)]
+	pub relative: bool,
+	// true: pointsを開始位置からの相対値として扱う
+}
+
+fn lerp(p1: [f32; 2], p2: [f32; 2], t: f32) -> [f32; 2] {
+	[p1[0] + (p2[0] - p1[0]) * t, p1[1] + (p2[1] - p1[1]) * t]
+}
+
+fn bezier(points: &[[f32; 2]], t: f32) -> [f32; 2] {
+	// de Casteljau
+	let mut buf = points.to_vec();
+	let n = buf.len();
+	for i in 1..n {
+		for j in 0..(n - i) {
+			buf[j] = lerp(buf[j], buf[j + 1], t);
+		}
+	}
+	buf[0]
+}
+
+fn catmull_rom(points: &[[f32; 2]], t: f32) -> [f32; 2] {
+	let seg_n = points.len() - 1;
+	let f = t * seg_n as f32;
+	let i = (f as usize).min(seg_n - 1);
+	let u = f - i as f32;
+
+	// 端は同じ点を繰り返す
+	let p0 = points[if i == 0 { 0 } else { i - 1 }];
+	let p1 = points[i];
+	let p2 = points[i + 1];
+	let p3 = points[(i + 2).min(seg_n)];
+
+	let uu = u * u;
+	let uuu = uu * u;
+	let mut p = [0.0; 2];
+	for k in 0..2 {
+		p[k] = 0.5 * (
+			2.0 * p1[k]
+			+ (-p0[k] + p2[k]) * u
+			+ (2.0 * p0[k] - 5.0 * p1[k] + 4.0 * p2[k] - p3[k]) * uu
+			+ (-p0[k] + 3.0 * p1[k] - 3.0 * p2[k] + p3[k]) * uuu
+		);
+	}
+	p
+}
+
+impl PathElement {
+	pub fn validate(&self) -> GameResult<()> {
+		if self.points.is_empty() {
+			return Err(GameError::ResourceLoadError("path: points is empty".to_owned()));
+		}
+		if self.duration == 0 {
+			return Err(GameError::ResourceLoadError("path: duration must be 1 or more".to_owned()));
+		}
+		Ok(())
+	}
+
+	// origin（開始位置）から見た t (0.0 ~ 1.0) の位置
+	pub fn point_at(&self, origin: [f32; 2], t: f32) -> [f32; 2] {
+		let t = self.easing.apply(t.max(0.0).min(1.0));
+
+		let mut control = Vec::with_capacity(self.points.len() + 1);
+		control.push(origin);
+		for p in &self.points {
+			if self.relative {
+				control.push([origin[0] + p[0], origin[1] + p[1]]);
+			} else {
+				control.push(*p);
+			}
+		}
+
+		match self.kind {
+			PathKind::MoveTo => lerp(origin, control[control.len() - 1], t),
+			PathKind::Bezier => bezier(&control, t),
+			PathKind::CatmullRom => catmull_rom(&control, t),
+		}
+	}
+
+	pub fn end_count(&self) -> u32 {
+		self.count + self.duration
+	}
+
+	// Stageの座標をWindowの座標に合わせる
+	pub fn scale(&mut self, x: f32, y: f32) {
+		for p in &mut self.points {
+			p[0] *= x;
+			p[1] *= y;
+		}
+	}
+
+	// 隊列のずれを加える（相対値の場合は開始位置がずれるので何もしない）
+	pub fn shift(&mut self, shift_point: [f32; 2]) {
+		if self.relative {
+			return;
+		}
+		for p in &mut self.points {
+			p[0] += shift_point[0];
+			p[1] += shift_point[1];
+		}
+	}
+}
+
+// 経路中ならActorの位置を更新してtrueを返す
+// velocityは経路上の移動量から求めるので, 経路が終わってもそのまま動き続ける
+pub fn update(actor: &mut Actor, count: u32, dt: f32) -> bool {
+	let mut next = None;
+	for pe in &actor.path {
+		if count == pe.count {
+			actor.path_origin = actor.point;
+		}
+		if pe.count <= count && count < pe.end_count() {
+			let t = (count + 1 - pe.count) as f32 / pe.duration as f32;
+			next = Some(pe.point_at(actor.path_origin, t));
+			break;
+		}
+	}
+
+	match next {
+		Some(p) => {
+			actor.velocity[0] = (p[0] - actor.point[0]) / dt;
+			actor.velocity[1] = (p[1] - actor.point[1]) / dt;
+			actor.point = p;
+			true
+		},
+		None => false,
+	}
+}
@@ -0,0 +1,179 @@
+use ggez::graphics;
+use ggez::event::{Keycode, Mod};
+use ggez::{Context, GameResult};
+
+use enemy_type::{EnemyTypes, SPECIAL_CHAR_TYPES};
+use {load_stage, Actor, Stage};
+use {STAGE_UP, STAGE_DOWN, STAGE_LEFT, STAGE_RIGHT};
+
+// story.json（またはstory.jsonと同じ書き方のファイル）のEnemyの軌道を描画するツール
+// 実際のゲームと同じ更新処理で軌道を求める
+//
+// Left, Right: 表示するEnemyを切り替え
+// Space: 全ての軌道を表示
+// Escape: 終了
+
+const SIMULATE_COUNT: u32 = 1200;
+// 軌道を求める最大count
+const MARK_INTERVAL: u32 = 30;
+// 軌道上に印をつける間隔
+
+struct Trajectory {
+	count: u32,
+	// 出現count
+	char_type: String,
+	points: Vec<[f32; 2]>,
+	// 1count毎の位置
+	events: Vec<[f32; 2]>,
+	// moving, pathが切り替わった位置
+}
+
+fn simulate(stage: &Stage, enemy_types: &EnemyTypes) -> Vec<Trajectory> {
+	let seconds = 1.0 / 60.0;
+	let mut st = stage.clone();
+	let mut trajectories = Vec::new();
+	loop {
+		let mut enemy = st.spawn_enemy(enemy_types);
+		let mut points = Vec::new();
+		let mut events = Vec::new();
+		for count in st.count..(st.count + SIMULATE_COUNT) {
+			let changed = enemy.moving.iter().any(|m| m.count == count)
+				|| enemy.path.iter().any(|p| p.count == count);
+			if changed {
+				events.push(enemy.point);
+			}
+			Actor::apply_moving(&mut enemy, count);
+			Actor::update_point_enemy(&mut enemy, count, seconds);
+			points.push(enemy.point);
+			if enemy.life <= 0.0 {
+				break;
+			}
+		}
+		trajectories.push(Trajectory {
+			count: st.count,
+			char_type: st.char_type.clone(),
+			points,
+			events,
+		});
+		if !st.next_in_formation() {
+			break;
+		}
+	}
+	trajectories
+}
+
+pub struct PathPreview {
+	entries: Vec<Vec<Trajectory>>,
+	// Stageの要素毎の軌道（隊列分）
+	selected: usize,
+	show_all: bool,
+	font: graphics::Font,
+}
+
+impl PathPreview {
+	pub fn new(ctx: &mut Context, path: &str, stage_name: &str) -> GameResult<PathPreview> {
+		let enemy_types = EnemyTypes::load("resources/enemy_type.json")?;
+		let (stage, _) = load_stage(path, stage_name, &enemy_types)?;
+
+		let mut entries = Vec::new();
+		for st in &stage {
+			if SPECIAL_CHAR_TYPES.contains(&st.char_type.as_str()) {
+				continue;
+			}
+			entries.push(simulate(st, &enemy_types));
+		}
+
+		Ok(PathPreview {
+			entries,
+			selected: 0,
+			show_all: false,
+			font: graphics::Font::new(ctx, "/SoberbaSerif-Regular.ttf", 18)?,
+		})
+	}
+
+	fn draw_entry(ctx: &mut Context, entry: &[Trajectory], selected: bool) -> GameResult<()> {
+		let c = if selected { 255 } else { 90 };
+		graphics::set_color(ctx, graphics::Color::from((c, c, c, 255)))?;
+		for tr in entry {
+			let points: Vec<graphics::Point2> = tr.points.iter()
+				.map(|p| graphics::Point2::new(p[0], p[1]))
+				.collect();
+			if points.len() >= 2 {
+				graphics::line(ctx, &points, 2.0)?;
+			}
+			if !selected {
+				continue;
+			}
+			for p in points.iter().step_by(MARK_INTERVAL as usize) {
+				graphics::circle(ctx, graphics::DrawMode::Fill, *p, 3.0, 0.5)?;
+			}
+			for e in &tr.events {
+				graphics::circle(
+					ctx,
+					graphics::DrawMode::Line(1.0),
+					graphics::Point2::new(e[0], e[1]),
+					7.0,
+					0.5,
+				)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl ggez::event::EventHandler for PathPreview {
+	fn update(&mut self, _ctx: &mut Context) -> GameResult<()> {
+		Ok(())
+	}
+
+	fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+		graphics::clear(ctx);
+
+		// stageの枠
+		graphics::set_color(ctx, graphics::Color::from((80, 80, 160, 255)))?;
+		graphics::rectangle(
+			ctx,
+			graphics::DrawMode::Line(1.0),
+			graphics::Rect::new(STAGE_LEFT, STAGE_UP, STAGE_RIGHT - STAGE_LEFT, STAGE_DOWN - STAGE_UP),
+		)?;
+
+		if self.show_all {
+			for (i, entry) in self.entries.iter().enumerate() {
+				if i != self.selected {
+					PathPreview::draw_entry(ctx, entry, false)?;
+				}
+			}
+		}
+		if let Some(entry) = self.entries.get(self.selected) {
+			PathPreview::draw_entry(ctx, entry, true)?;
+
+			graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))?;
+			let dis_str = format!(
+				"{}/{}  count: {}  char_type: {}",
+				self.selected + 1,
+				self.entries.len(),
+				entry[0].count,
+				entry[0].char_type,
+				);
+			let display = graphics::Text::new(ctx, &dis_str, &self.font)?;
+			graphics::draw(ctx, &display, graphics::Point2::new(STAGE_RIGHT + 20.0, STAGE_UP), 0.0)?;
+		}
+
+		graphics::present(ctx);
+		Ok(())
+	}
+
+	fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+		let len = self.entries.len();
+		if len == 0 {
+			return;
+		}
+		match keycode {
+			Keycode::Right => self.selected = (self.selected + 1) % len,
+			Keycode::Left => self.selected = (self.selected + len - 1) % len,
+			Keycode::Space => self.show_all = !self.show_all,
+			Keycode::Escape => ctx.quit().unwrap(),
+			_ => (),
+		}
+	}
+}
@@ -0,0 +1,83 @@
+use ggez::graphics;
+use ggez::{Context, GameResult};
+
+use bindings::Action;
+use fonts::{Align, TextCache};
+use locale::Strings;
+use menu::{Menu, MenuEvent};
+use {RELATIVE_X, RELATIVE_Y, STAGE_UP, STAGE_DOWN, STAGE_LEFT, STAGE_RIGHT};
+
+// Pauseメニュー
+// Escape, またはWindowのフォーカスが外れた時に開く
+// 開いている間はゲームのcountを進めない
+// MainでBomb, Pauseを押すと再開
+
+const MAIN_ITEMS: [&str; 5] = ["pause.resume", "pause.restart", "pause.title", "pause.save_replay", "pause.options"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PauseAction {
+	None,
+	Resume,
+	Restart,
+	Title,
+	SaveReplay,
+	Options,
+}
+
+#[derive(Debug, Clone)]
+pub struct PauseMenu {
+	main: Menu,
+	message: Option<String>,
+	// Save replayの結果など
+}
+
+impl PauseMenu {
+	pub fn new() -> PauseMenu {
+		PauseMenu {
+			main: Menu::new(&MAIN_ITEMS),
+			message: None,
+		}
+	}
+
+	pub fn open(&mut self) {
+		self.main.reset();
+		self.message = None;
+	}
+
+	pub fn set_message(&mut self, message: String) {
+		self.message = Some(message);
+	}
+
+	pub fn input(&mut self, action: Action) -> PauseAction {
+		match self.main.input(action) {
+			MenuEvent::None => PauseAction::None,
+			MenuEvent::Confirm(0) | MenuEvent::Cancel => PauseAction::Resume,
+			MenuEvent::Confirm(1) => PauseAction::Restart,
+			MenuEvent::Confirm(2) => PauseAction::Title,
+			MenuEvent::Confirm(3) => PauseAction::SaveReplay,
+			MenuEvent::Confirm(_) => PauseAction::Options,
+		}
+	}
+
+	pub fn draw(&self, ctx: &mut Context, strings: &Strings, texts: &mut TextCache) -> GameResult<()> {
+		// Stageを暗くする
+		graphics::set_color(ctx, graphics::Color::from((0, 0, 0, 160)))?;
+		graphics::rectangle(
+			ctx,
+			graphics::DrawMode::Fill,
+			graphics::Rect::new(STAGE_LEFT, STAGE_UP, STAGE_RIGHT - STAGE_LEFT, STAGE_DOWN - STAGE_UP),
+		)?;
+		graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))?;
+
+		let center = (STAGE_LEFT + STAGE_RIGHT) / 2.0;
+		texts.draw(ctx, strings.get("pause.heading"), (50.0 * RELATIVE_X) as u32, [center, 250.0 * RELATIVE_Y], Align::Center, None)?;
+
+		let size = (26.0 * RELATIVE_X) as u32;
+		self.main.draw(ctx, strings, texts, [300.0 * RELATIVE_X, 380.0 * RELATIVE_Y], size, 45.0 * RELATIVE_Y)?;
+
+		if let Some(ref m) = self.message {
+			texts.draw(ctx, m, (18.0 * RELATIVE_X) as u32, [200.0 * RELATIVE_X, 700.0 * RELATIVE_Y], Align::Left, None)?;
+		}
+		Ok(())
+	}
+}
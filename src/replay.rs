@@ -0,0 +1,165 @@
+use ggez::{Context, GameResult};
+use ggez::error::GameError;
+
+use serde_json;
+
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use InputState;
+use mode::{Character, Difficulty, PracticeStart, SpellStart};
+
+// リプレイの記録
+// Gaming, GamingBossのcount毎に入力を1つ記録する
+// Pause中はcountが進まないので記録されない（止めていた時間は残らない）
+//
+// 乱数のseedも記録するので, 同じ入力を与えれば同じ弾幕になる
+//
+// version 1: 入力とseedだけ
+// version 2: 難易度, 自機を追加
+
+const VERSION: u32 = 2;
+const REPLAY_DIR: &str = "replays";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Replay {
+	version: u32,
+	stage: String,
+	start_count: u32,
+	// 記録を始めたcount（story.jsonのinitial_count, Practiceではsectionのcount）
+	seed: u64,
+	// 乱数のseed
+	difficulty: Difficulty,
+	character: Character,
+	#[serde(default)]
+	practice: Option<PracticeStart>,
+	#[serde(default)]
+	spell: Option<SpellStart>,
+	inputs: Vec<u8>,
+	// count毎の入力 InputStateのbit
+}
+
+impl Replay {
+	pub fn new(
+		stage: &str,
+		start_count: u32,
+		seed: u64,
+		difficulty: Difficulty,
+		character: Character,
+		practice: Option<PracticeStart>,
+		spell: Option<SpellStart>,
+		) -> Replay {
+		Replay {
+			version: VERSION,
+			stage: stage.to_owned(),
+			start_count,
+			seed,
+			difficulty,
+			character,
+			practice,
+			spell,
+			inputs: Vec::new(),
+		}
+	}
+
+	pub fn load<P: AsRef<Path>>(path: P) -> GameResult<Replay> {
+		let name = path.as_ref().display().to_string();
+		let mut s = String::new();
+		File::open(&path)?.read_to_string(&mut s)?;
+		let replay: Replay = serde_json::from_str(&s)
+			.map_err(|e| GameError::ResourceLoadError(format!("{}: {}", name, e)))?;
+		if replay.version != VERSION {
+			return Err(GameError::ResourceLoadError(
+				format!("{}: unsupported replay version {}", name, replay.version)
+			));
+		}
+		Ok(replay)
+	}
+
+	// ユーザーの設定ディレクトリのREPLAY_DIR
+	pub fn dir(ctx: &Context) -> PathBuf {
+		ctx.filesystem.get_user_config_dir().join(REPLAY_DIR)
+	}
+
+	// dirのリプレイ 新しい順
+	pub fn list(dir: &Path) -> Vec<PathBuf> {
+		let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+			Ok(dir) => dir
+				.filter_map(|e| e.ok())
+				.map(|e| e.path())
+				.filter(|p| p.extension().map_or(false, |x| x == "json"))
+				.collect(),
+			Err(_) => Vec::new(),
+		};
+		paths.sort();
+		paths.reverse();
+		paths
+	}
+
+	pub fn seed(&self) -> u64 {
+		self.seed
+	}
+
+	pub fn difficulty(&self) -> Difficulty {
+		self.difficulty
+	}
+
+	pub fn character(&self) -> Character {
+		self.character
+	}
+
+	pub fn practice(&self) -> Option<&PracticeStart> {
+		self.practice.as_ref()
+	}
+
+	pub fn spell(&self) -> Option<&SpellStart> {
+		self.spell.as_ref()
+	}
+
+	// 記録したcount数
+	pub fn len(&self) -> usize {
+		self.inputs.len()
+	}
+
+	pub fn record(&mut self, input: &InputState) {
+		let bits = [input.up, input.down, input.left, input.right, input.shift, input.shot, input.bomb];
+		let mut b = 0;
+		for (i, on) in bits.iter().enumerate() {
+			if *on {
+				b |= 1 << i;
+			}
+		}
+		self.inputs.push(b);
+	}
+
+	// i番目のcountの入力 記録が終わっていればNone
+	pub fn input(&self, i: usize) -> Option<InputState> {
+		self.inputs.get(i).map(|b| InputState {
+			up: b & 1 != 0,
+			down: b & 1 << 1 != 0,
+			left: b & 1 << 2 != 0,
+			right: b & 1 << 3 != 0,
+			shift: b & 1 << 4 != 0,
+			shot: b & 1 << 5 != 0,
+			bomb: b & 1 << 6 != 0,
+		})
+	}
+
+	// dirに書き出して, ファイル名を返す
+	pub fn save(&self, dir: &Path) -> GameResult<String> {
+		let secs = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|d| d.as_secs())
+			.unwrap_or(0);
+		let path = dir.join(format!("{}_{}.json", self.stage, secs));
+		let s = serde_json::to_string(self)
+			.map_err(|e| GameError::UnknownError(format!("replay: {}", e)))?;
+		fs::create_dir_all(dir)?;
+		let mut f = File::create(&path)?;
+		f.write_all(s.as_bytes())?;
+		Ok(path.display().to_string())
+	}
+}
@@ -0,0 +1,144 @@
+use std::time::{Duration, Instant};
+
+// 固定countのゲームループ
+// 1フレームに進めるcount数を policy で決める
+//
+// Slowdown: 1フレームに1countまで 処理が重いとゲームが遅くなる
+// CatchUp: 遅れたらmax_stepsまで追いつく それ以上の遅れは捨てる
+// Interpolated: CatchUpと同じように進め, 描画は前のcountとの間を補間する
+
+pub const FPS: u32 = 60;
+const INTERPOLATED_MAX_STEPS: u32 = 5;
+// Interpolatedで1フレームに進める最大count
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepPolicy {
+	Slowdown,
+	CatchUp {
+		max_steps: u32,
+	},
+	Interpolated,
+}
+
+impl StepPolicy {
+	pub const ALL: [StepPolicy; 3] = [
+		StepPolicy::Slowdown,
+		StepPolicy::CatchUp { max_steps: 4 },
+		StepPolicy::Interpolated,
+	];
+
+	pub fn name(&self) -> String {
+		match *self {
+			StepPolicy::Slowdown => "slowdown".to_owned(),
+			StepPolicy::CatchUp { max_steps } => format!("catch-up (max {})", max_steps),
+			StepPolicy::Interpolated => "interpolated".to_owned(),
+		}
+	}
+
+	// ALLの次のpolicy
+	pub fn next(&self) -> StepPolicy {
+		let i = StepPolicy::ALL.iter().position(|p| p == self).unwrap_or(0);
+		StepPolicy::ALL[(i + 1) % StepPolicy::ALL.len()]
+	}
+
+	fn max_steps(&self) -> u32 {
+		match *self {
+			StepPolicy::Slowdown => 1,
+			StepPolicy::CatchUp { max_steps } => max_steps.max(1),
+			StepPolicy::Interpolated => INTERPOLATED_MAX_STEPS,
+		}
+	}
+}
+
+fn to_secs(d: Duration) -> f64 {
+	d.as_secs() as f64 + d.subsec_nanos() as f64 * 1e-9
+}
+
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+	pub policy: StepPolicy,
+	step: f64,
+	// 1countの秒数
+	accumulator: f64,
+	// まだ進めていない時間
+	last: Option<Instant>,
+	steps: u32,
+	// 直前のフレームで進めたcount数
+	fps: f64,
+	update_time: f64,
+	// 1フレームのupdateにかかった秒（平均）
+	frame_start: Option<Instant>,
+}
+
+impl Scheduler {
+	pub fn new(policy: StepPolicy) -> Scheduler {
+		Scheduler {
+			policy,
+			step: 1.0 / FPS as f64,
+			accumulator: 0.0,
+			last: None,
+			steps: 0,
+			fps: FPS as f64,
+			update_time: 0.0,
+			frame_start: None,
+		}
+	}
+
+	// 1countの秒数
+	pub fn dt(&self) -> f32 {
+		self.step as f32
+	}
+
+	// 前のフレームからの経過時間から, このフレームで進めるcount数を返す
+	pub fn begin_frame(&mut self) -> u32 {
+		let now = Instant::now();
+		let elapsed = match self.last {
+			Some(last) => to_secs(now.duration_since(last)),
+			None => self.step,
+		};
+		self.last = Some(now);
+		self.frame_start = Some(now);
+		if elapsed > 0.0 {
+			self.fps = self.fps * 0.95 + (1.0 / elapsed) * 0.05;
+		}
+
+		self.accumulator += elapsed;
+		let max = self.policy.max_steps();
+		let mut steps = (self.accumulator / self.step) as u32;
+		if steps > max {
+			// 追いつけない分は捨てる
+			steps = max;
+			self.accumulator = self.step * steps as f64;
+		}
+		self.accumulator -= self.step * steps as f64;
+		self.steps = steps;
+		steps
+	}
+
+	// updateにかかった時間を記録
+	pub fn end_frame(&mut self) {
+		if let Some(start) = self.frame_start.take() {
+			let t = to_secs(start.elapsed());
+			self.update_time = self.update_time * 0.95 + t * 0.05;
+		}
+	}
+
+	// 描画の補間の割合 0.0（前のcount） ~ 1.0（今のcount）
+	pub fn alpha(&self) -> f32 {
+		match self.policy {
+			StepPolicy::Interpolated => (self.accumulator / self.step).max(0.0).min(1.0) as f32,
+			_ => 1.0,
+		}
+	}
+
+	// FPS, updateの時間, policyの表示用
+	pub fn overlay_text(&self) -> String {
+		format!(
+			"FPS: {:.1}  update: {:.2}ms x{}  {}",
+			self.fps,
+			self.update_time * 1000.0,
+			self.steps,
+			self.policy.name(),
+		)
+	}
+}
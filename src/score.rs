@@ -0,0 +1,144 @@
+use ggez::{Context, GameResult};
+use ggez::error::GameError;
+
+use serde_json;
+
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use mode::{Character, Difficulty, SpellStart};
+
+// ハイスコアの記録
+// 難易度毎に上位MAX_ENTRIES件を残す
+// Practiceのスコアは通常のスコアと別に残す
+// Spell practiceはphase, 難易度毎の挑戦回数と取得回数を残す
+// ユーザーの設定ディレクトリのSCORE_FILEに保存する
+// 読めない時は.bakに移して空から始める（次の保存で壊れたファイルを上書きしない）
+
+const SCORE_FILE: &str = "scores.json";
+const MAX_ENTRIES: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScoreEntry {
+	pub score: u32,
+	pub difficulty: Difficulty,
+	pub character: Character,
+	pub cleared: bool,
+	pub time: u64,
+	// 記録した時刻（UNIX時間の秒）
+	#[serde(default)]
+	pub section: Option<String>,
+	// Practice: 始めたsectionの名前
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpellRecord {
+	pub spell: SpellStart,
+	pub difficulty: Difficulty,
+	pub attempts: u32,
+	pub captures: u32,
+	// 被弾, Bombをせずに倒した回数
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct HighScores {
+	entries: Vec<ScoreEntry>,
+	#[serde(default)]
+	practice: Vec<ScoreEntry>,
+	#[serde(default)]
+	spells: Vec<SpellRecord>,
+}
+
+impl HighScores {
+	pub fn path(ctx: &Context) -> PathBuf {
+		ctx.filesystem.get_user_config_dir().join(SCORE_FILE)
+	}
+
+	// ファイルが無い, 読めない時は空
+	pub fn load(path: &Path) -> HighScores {
+		let mut s = String::new();
+		if File::open(path).and_then(|mut f| f.read_to_string(&mut s)).is_err() {
+			return HighScores::default();
+		}
+		match serde_json::from_str(&s) {
+			Ok(scores) => scores,
+			Err(e) => {
+				eprintln!("{}: {}", path.display(), e);
+				let _ = fs::rename(path, path.with_extension("json.bak"));
+				HighScores::default()
+			},
+		}
+	}
+
+	pub fn save(&self, path: &Path) -> GameResult<()> {
+		let s = serde_json::to_string_pretty(self)
+			.map_err(|e| GameError::UnknownError(format!("{}: {}", path.display(), e)))?;
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		let mut f = File::create(path)?;
+		f.write_all(s.as_bytes())?;
+		Ok(())
+	}
+
+	// 記録して, 難易度毎にMAX_ENTRIES件まで残す
+	// section: Practiceの時はsectionの名前
+	pub fn add(
+		&mut self,
+		score: u32,
+		difficulty: Difficulty,
+		character: Character,
+		cleared: bool,
+		section: Option<String>,
+		) {
+		let time = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|d| d.as_secs())
+			.unwrap_or(0);
+		let entries = if section.is_some() { &mut self.practice } else { &mut self.entries };
+		entries.push(ScoreEntry { score, difficulty, character, cleared, time, section });
+		entries.sort_by(|a, b| b.score.cmp(&a.score));
+
+		let mut kept = [0; 4];
+		entries.retain(|e| {
+			let i = Difficulty::ALL.iter().position(|d| *d == e.difficulty).unwrap_or(0);
+			kept[i] += 1;
+			kept[i] <= MAX_ENTRIES
+		});
+	}
+
+	fn spell_mut(&mut self, spell: &SpellStart, difficulty: Difficulty) -> &mut SpellRecord {
+		let i = match self.spells.iter().position(|r| r.spell == *spell && r.difficulty == difficulty) {
+			Some(i) => i,
+			None => {
+				self.spells.push(SpellRecord { spell: spell.clone(), difficulty, attempts: 0, captures: 0 });
+				self.spells.len() - 1
+			},
+		};
+		&mut self.spells[i]
+	}
+
+	pub fn add_spell_attempt(&mut self, spell: &SpellStart, difficulty: Difficulty) {
+		self.spell_mut(spell, difficulty).attempts += 1;
+	}
+
+	pub fn add_spell_capture(&mut self, spell: &SpellStart, difficulty: Difficulty) {
+		self.spell_mut(spell, difficulty).captures += 1;
+	}
+
+	// [取得回数, 挑戦回数]
+	pub fn spell(&self, spell: &SpellStart, difficulty: Difficulty) -> [u32; 2] {
+		self.spells.iter()
+			.find(|r| r.spell == *spell && r.difficulty == difficulty)
+			.map_or([0, 0], |r| [r.captures, r.attempts])
+	}
+
+	// 難易度の記録を高い順に
+	pub fn top(&self, difficulty: Difficulty, practice: bool) -> Vec<&ScoreEntry> {
+		let entries = if practice { &self.practice } else { &self.entries };
+		entries.iter().filter(|e| e.difficulty == difficulty).collect()
+	}
+}
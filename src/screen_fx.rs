@@ -0,0 +1,184 @@
+use ggez::graphics;
+use ggez::{Context, GameResult};
+
+use settings::MAX_SCREEN_EFFECTS;
+use {RELATIVE_X, RELATIVE_Y, SCREEN_WIDTH, SCREEN_HEIGHT, STAGE_DOWN, STAGE_LEFT, STAGE_RIGHT, STAGE_UP};
+
+// 画面の揺れ, フラッシュ, フェード
+// 揺れ: Bossのphaseの終わり, 被弾  フラッシュ: Bomb  フェード: WindowStateの切り替え
+// 強さはSettingsのscreen_effects 揺れ, フラッシュに掛ける 0なら全て無し（フェードもしない）
+//
+// 1countに1回updateする 揺れは描画の座標をずらすだけなのでゲームに影響しない
+
+// 始めの値から0へ減っていく
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Decay {
+	value: f32,
+	frames: u32,
+	count: u32,
+}
+
+impl Decay {
+	// 今より強い時だけ始め直す
+	fn start(&mut self, value: f32, frames: u32) {
+		if frames > 0 && value >= self.value() {
+			*self = Decay { value, frames, count: 0 };
+		}
+	}
+
+	fn value(&self) -> f32 {
+		if self.count >= self.frames {
+			0.0
+		} else {
+			self.value * (1.0 - self.count as f32 / self.frames as f32)
+		}
+	}
+
+	fn update(&mut self) {
+		if self.count < self.frames {
+			self.count += 1;
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenFx {
+	intensity: f32,
+	// 0.0 ~ 1.0
+	shake: Decay,
+	// 揺れの幅 (px)
+	flash: Decay,
+	// 白の不透明度 0.0 ~ 1.0
+	fade: f32,
+	// 黒の不透明度 0.0 ~ 1.0
+	fade_step: f32,
+	// 1countに変える量 負なら明るくなる
+	count: u32,
+	// 揺れの向きに使う
+}
+
+impl ScreenFx {
+	pub fn new(screen_effects: u32) -> ScreenFx {
+		let mut fx = ScreenFx {
+			intensity: 1.0,
+			shake: Decay::default(),
+			flash: Decay::default(),
+			fade: 0.0,
+			fade_step: 0.0,
+			count: 0,
+		};
+		fx.set_intensity(screen_effects);
+		fx
+	}
+
+	// screen_effects: 0 ~ MAX_SCREEN_EFFECTS
+	pub fn set_intensity(&mut self, screen_effects: u32) {
+		self.intensity = screen_effects.min(MAX_SCREEN_EFFECTS) as f32 / MAX_SCREEN_EFFECTS as f32;
+		if self.intensity <= 0.0 {
+			self.clear();
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.shake = Decay::default();
+		self.flash = Decay::default();
+		self.fade = 0.0;
+		self.fade_step = 0.0;
+	}
+
+	// amplitude: 揺れの幅 (px)
+	pub fn shake(&mut self, amplitude: f32, frames: u32) {
+		let a = amplitude * self.intensity;
+		self.shake.start(a, frames);
+	}
+
+	// alpha: 白の不透明度 0.0 ~ 1.0
+	pub fn flash(&mut self, alpha: f32, frames: u32) {
+		let a = alpha * self.intensity;
+		self.flash.start(a, frames);
+	}
+
+	// 黒からframes countで明るくする
+	pub fn fade_in(&mut self, frames: u32) {
+		if self.intensity <= 0.0 || frames == 0 {
+			self.fade = 0.0;
+			self.fade_step = 0.0;
+			return;
+		}
+		self.fade = 1.0;
+		self.fade_step = -1.0 / frames as f32;
+	}
+
+	// 今の明るさからframes countで黒にする
+	pub fn fade_out(&mut self, frames: u32) {
+		if self.intensity <= 0.0 || frames == 0 {
+			return;
+		}
+		self.fade_step = 1.0 / frames as f32;
+	}
+
+	// WindowStateが変わってからcount経った時の重ねる画像の色 frames countで不透明にする
+	pub fn overlay_color(&self, count: u32, frames: u32) -> graphics::Color {
+		let a = if self.intensity <= 0.0 || count >= frames {
+			1.0
+		} else {
+			count as f32 / frames as f32
+		};
+		graphics::Color::new(1.0, 1.0, 1.0, a)
+	}
+
+	pub fn update(&mut self) {
+		self.count = self.count.wrapping_add(1);
+		self.shake.update();
+		self.flash.update();
+		self.fade = (self.fade + self.fade_step).max(0.0).min(1.0);
+		if self.fade <= 0.0 || self.fade >= 1.0 {
+			self.fade_step = 0.0;
+		}
+	}
+
+	// 揺れの分だけ描画の座標をずらす restoreで戻す
+	pub fn apply(&self, ctx: &mut Context) -> GameResult<()> {
+		let a = self.shake.value();
+		if a <= 0.0 {
+			return Ok(());
+		}
+		let t = self.count as f32;
+		let dx = a * (t * 2.3).sin() * RELATIVE_X;
+		let dy = a * (t * 3.1).cos() * RELATIVE_Y;
+		graphics::set_screen_coordinates(ctx, graphics::Rect::new(-dx, -dy, SCREEN_WIDTH, SCREEN_HEIGHT))
+	}
+
+	pub fn restore(&self, ctx: &mut Context) -> GameResult<()> {
+		graphics::set_screen_coordinates(ctx, graphics::Rect::new(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT))
+	}
+
+	// ステージの枠の中を白くする
+	pub fn draw_flash(&self, ctx: &mut Context) -> GameResult<()> {
+		let a = self.flash.value();
+		if a <= 0.0 {
+			return Ok(());
+		}
+		graphics::set_color(ctx, graphics::Color::new(1.0, 1.0, 1.0, a))?;
+		graphics::rectangle(
+			ctx,
+			graphics::DrawMode::Fill,
+			graphics::Rect::new(STAGE_LEFT, STAGE_UP, STAGE_RIGHT - STAGE_LEFT, STAGE_DOWN - STAGE_UP),
+		)?;
+		graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))
+	}
+
+	// 画面全体を黒くする
+	pub fn draw_fade(&self, ctx: &mut Context) -> GameResult<()> {
+		if self.fade <= 0.0 {
+			return Ok(());
+		}
+		graphics::set_color(ctx, graphics::Color::new(0.0, 0.0, 0.0, self.fade))?;
+		graphics::rectangle(
+			ctx,
+			graphics::DrawMode::Fill,
+			graphics::Rect::new(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT),
+		)?;
+		graphics::set_color(ctx, graphics::Color::from((255, 255, 255, 255)))
+	}
+}
@@ -0,0 +1,210 @@
+use ggez::{Context, GameResult};
+use ggez::conf::{FullscreenType, WindowMode};
+use ggez::error::GameError;
+use ggez::graphics;
+
+use serde_json;
+
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use bindings::Bindings;
+use mode::Difficulty;
+use {SCREEN_WIDTH, SCREEN_HEIGHT};
+
+// 設定ファイル
+// ユーザーの設定ディレクトリのSETTINGS_FILEに保存する
+// 無い時は初期値で始める
+// 読めない, versionが新しい時は.bakに移して初期値で始める（次の保存で上書きしない）
+// 項目が足りない時はその項目だけ初期値にする
+//
+// version 1: bindingsだけ
+// version 2: 画面, 音量, 難易度, 当たり判定, 言語を追加
+// version 3: 画面効果の強さを追加
+
+const VERSION: u32 = 3;
+const SETTINGS_FILE: &str = "settings.json";
+pub const WINDOW_SCALES: [f32; 5] = [0.75, 1.0, 1.25, 1.5, 2.0];
+// SCREEN_WIDTH, SCREEN_HEIGHTに掛ける
+pub const VOLUME_STEP: u32 = 10;
+pub const MAX_VOLUME: u32 = 100;
+pub const SCREEN_EFFECTS_STEP: u32 = 25;
+pub const MAX_SCREEN_EFFECTS: u32 = 100;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+	#[serde(rename = "en")]
+	English,
+	#[serde(rename = "ja")]
+	Japanese,
+}
+
+impl Language {
+	pub const ALL: [Language; 2] = [Language::English, Language::Japanese];
+
+	// その言語での名前
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Language::English => "English",
+			Language::Japanese => "日本語",
+		}
+	}
+
+	// resources/langのファイル名
+	pub fn code(&self) -> &'static str {
+		match *self {
+			Language::English => "en",
+			Language::Japanese => "ja",
+		}
+	}
+}
+
+impl Default for Language {
+	fn default() -> Language {
+		Language::English
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+	version: u32,
+	pub window_scale: f32,
+	// WINDOW_SCALESのどれか
+	pub fullscreen: bool,
+	pub vsync: bool,
+	pub bgm_volume: u32,
+	pub se_volume: u32,
+	// 0 ~ MAX_VOLUME
+	pub difficulty: Difficulty,
+	// Titleで最初に選ぶ難易度
+	pub show_hitbox: bool,
+	// 低速移動でない時も当たり判定を表示する
+	pub language: Language,
+	pub screen_effects: u32,
+	// 揺れ, フラッシュ, フェードの強さ 0 ~ MAX_SCREEN_EFFECTS 0なら無し
+	pub bindings: Bindings,
+}
+
+impl Default for Settings {
+	fn default() -> Settings {
+		Settings {
+			version: VERSION,
+			window_scale: 1.0,
+			fullscreen: false,
+			vsync: true,
+			bgm_volume: 70,
+			se_volume: 70,
+			difficulty: Difficulty::default(),
+			show_hitbox: false,
+			language: Language::default(),
+			screen_effects: MAX_SCREEN_EFFECTS,
+			bindings: Bindings::default(),
+		}
+	}
+}
+
+impl Settings {
+	pub fn path(ctx: &Context) -> PathBuf {
+		ctx.filesystem.get_user_config_dir().join(SETTINGS_FILE)
+	}
+
+	pub fn load(path: &Path) -> Settings {
+		let mut s = String::new();
+		if File::open(path).and_then(|mut f| f.read_to_string(&mut s)).is_err() {
+			return Settings::default();
+		}
+		let mut settings: Settings = match serde_json::from_str(&s) {
+			Ok(settings) => settings,
+			Err(e) => {
+				eprintln!("{}: {}", path.display(), e);
+				let _ = fs::rename(path, path.with_extension("json.bak"));
+				return Settings::default();
+			},
+		};
+		if settings.version > VERSION {
+			eprintln!("{}: unsupported settings version {}", path.display(), settings.version);
+			let _ = fs::rename(path, path.with_extension("json.bak"));
+			return Settings::default();
+		}
+		settings.version = VERSION;
+		settings.sanitize();
+		settings
+	}
+
+	// 手で書き換えられた値を選べる値に戻す
+	fn sanitize(&mut self) {
+		if !WINDOW_SCALES.contains(&self.window_scale) {
+			self.window_scale = 1.0;
+		}
+		self.bgm_volume = self.bgm_volume.min(MAX_VOLUME);
+		self.se_volume = self.se_volume.min(MAX_VOLUME);
+		self.screen_effects = self.screen_effects.min(MAX_SCREEN_EFFECTS);
+		self.bindings.fill_defaults();
+	}
+
+	pub fn save(&self, path: &Path) -> GameResult<()> {
+		let s = serde_json::to_string_pretty(self)
+			.map_err(|e| GameError::UnknownError(format!("{}: {}", path.display(), e)))?;
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		let mut f = File::create(path)?;
+		f.write_all(s.as_bytes())?;
+		Ok(())
+	}
+
+	// Windowの大きさ, フルスクリーン, VSyncを反映する
+	// 描画は大きさに関わらずSCREEN_WIDTH x SCREEN_HEIGHTの座標で行う
+	pub fn apply_window(&self, ctx: &mut Context) -> GameResult<()> {
+		let w = (SCREEN_WIDTH * self.window_scale) as u32;
+		let h = (SCREEN_HEIGHT * self.window_scale) as u32;
+		let fullscreen = if self.fullscreen { FullscreenType::Desktop } else { FullscreenType::Off };
+		let mode = WindowMode::default()
+			.dimensions(w, h)
+			.min_dimensions(w, h)
+			.max_dimensions(w, h)
+			.fullscreen_type(fullscreen)
+			.vsync(self.vsync);
+		graphics::set_mode(ctx, mode)?;
+		graphics::set_screen_coordinates(ctx, graphics::Rect::new(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT))
+	}
+
+	// 左右で選ぶ値 dir: -1 or 1
+	pub fn step_scale(&mut self, dir: i32) {
+		let i = WINDOW_SCALES.iter().position(|s| *s == self.window_scale).unwrap_or(1) as i32;
+		let i = (i + dir).max(0).min(WINDOW_SCALES.len() as i32 - 1);
+		self.window_scale = WINDOW_SCALES[i as usize];
+	}
+
+	pub fn step_difficulty(&mut self, dir: i32) {
+		let n = Difficulty::ALL.len() as i32;
+		let i = Difficulty::ALL.iter().position(|d| *d == self.difficulty).unwrap_or(1) as i32;
+		self.difficulty = Difficulty::ALL[((i + dir + n) % n) as usize];
+	}
+
+	pub fn step_screen_effects(&mut self, dir: i32) {
+		self.screen_effects = if dir < 0 {
+			self.screen_effects.saturating_sub(SCREEN_EFFECTS_STEP)
+		} else {
+			(self.screen_effects + SCREEN_EFFECTS_STEP).min(MAX_SCREEN_EFFECTS)
+		};
+	}
+
+	pub fn step_language(&mut self, dir: i32) {
+		let n = Language::ALL.len() as i32;
+		let i = Language::ALL.iter().position(|l| *l == self.language).unwrap_or(0) as i32;
+		self.language = Language::ALL[((i + dir + n) % n) as usize];
+	}
+}
+
+// 音量を VOLUME_STEP ずつ変える
+pub fn step_volume(volume: u32, dir: i32) -> u32 {
+	if dir < 0 {
+		volume.saturating_sub(VOLUME_STEP)
+	} else {
+		(volume + VOLUME_STEP).min(MAX_VOLUME)
+	}
+}
@@ -0,0 +1,150 @@
+use bullet_store::Bullet;
+use shot_type::get_angle_from_points;
+use {RELATIVE_X, STAGE_UP, STAGE_DOWN, STAGE_LEFT, STAGE_RIGHT};
+
+// 敵弾の動き
+// 撃った後の弾の向き, 位置を変える（Bulletのbehaviorに持たせる）
+//
+// 角度はShotのvelocity[0]と同じ（0.0が真下, 1.0で半周）
+// angular_speed, turnは1秒あたりの角度
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Behavior {
+	None,
+	// velocity, accelのまま
+	Homing {
+		turn: f32,
+		// 1秒あたりに曲がれる角度の上限
+		duration: u32,
+		// 追尾するcount
+	},
+	// Playerの方へ曲がり続ける
+	ReAim {
+		delay: u32,
+		// 狙い直すcount
+		speed: f32,
+		// 狙い直した後の速さ
+	},
+	// 一度だけPlayerを狙い直す accelは0になる
+	Orbit {
+		parent: u32,
+		// 回る相手のActor.id
+		angular_speed: f32,
+		expand: f32,
+		// 1秒あたりに半径が広がる距離
+		duration: u32,
+		// 回るcount 過ぎると外側へ飛んでいく
+	},
+	// 撃ったEnemyの周りを回る 相手がいなくなっても外側へ飛んでいく
+	Curve {
+		from: u32,
+		until: u32,
+		turn: f32,
+	},
+	// from ~ until のcountの間 曲がる
+	Bounce {
+		times: u32,
+		// 残りの跳ね返る回数
+	},
+	// Stageの端で跳ね返る
+}
+
+impl Default for Behavior {
+	fn default() -> Behavior {
+		Behavior::None
+	}
+}
+
+// 角度を -1.0 ~ 1.0 に直す
+fn normalize_angle(angle: f32) -> f32 {
+	let mut a = angle % 2.0;
+	if a > 1.0 {
+		a -= 2.0;
+	} else if a < -1.0 {
+		a += 2.0;
+	}
+	a
+}
+
+// 1count分の動きを反映
+// parents: [Actor.id, 位置] 回る相手を探す
+// 位置まで決めた時（Orbit中）はtrue, update_point_polarは呼ばない
+pub fn update(shot: &mut Bullet, p_point: [f32; 2], parents: &[(u32, [f32; 2])], dt: f32) -> bool {
+	match shot.behavior {
+		Behavior::None => false,
+		Behavior::Homing { turn, duration } => {
+			if shot.count < duration {
+				let target = get_angle_from_points(shot.point, p_point);
+				let diff = normalize_angle(target - shot.velocity[0]);
+				let max = turn * dt;
+				shot.velocity[0] += diff.max(-max).min(max);
+			} else {
+				shot.behavior = Behavior::None;
+			}
+			false
+		},
+		Behavior::ReAim { delay, speed } => {
+			if shot.count >= delay {
+				shot.velocity = [get_angle_from_points(shot.point, p_point), speed];
+				shot.accel = [0.0; 2];
+				shot.behavior = Behavior::None;
+			}
+			false
+		},
+		Behavior::Orbit { parent, angular_speed, expand, duration } => {
+			let center = parents.iter().find(|p| p.0 == parent).map(|p| p.1);
+			let center = match center {
+				Some(c) if shot.count < duration => c,
+				_ => {
+					// 外側へ飛んでいく
+					shot.behavior = Behavior::None;
+					return false;
+				},
+			};
+			let dx = shot.point[0] - center[0];
+			let dy = shot.point[1] - center[1];
+			let dist = (dx * dx + dy * dy).sqrt();
+			let angle = if dist < 0.01 {
+				shot.velocity[0]
+			} else {
+				get_angle_from_points(center, shot.point)
+			} + angular_speed * dt;
+			let r = dist + expand * RELATIVE_X * dt;
+			let rad = angle * ::std::f32::consts::PI;
+			shot.point = [center[0] + r * rad.sin(), center[1] + r * rad.cos()];
+			shot.velocity[0] = angle;
+			true
+		},
+		Behavior::Curve { from, until, turn } => {
+			if shot.count >= until {
+				shot.behavior = Behavior::None;
+			} else if shot.count >= from {
+				shot.velocity[0] += turn * dt;
+			}
+			false
+		},
+		Behavior::Bounce { times } => {
+			if times == 0 {
+				shot.behavior = Behavior::None;
+				return false;
+			}
+			let rad = shot.velocity[0] * ::std::f32::consts::PI;
+			let (x_vel, y_vel) = (rad.sin(), rad.cos());
+			let p = shot.point;
+			let mut bounced = false;
+			if (p[0] < STAGE_LEFT && x_vel < 0.0) || (p[0] > STAGE_RIGHT && x_vel > 0.0) {
+				shot.velocity[0] = -shot.velocity[0];
+				bounced = true;
+			}
+			if (p[1] < STAGE_UP && y_vel < 0.0) || (p[1] > STAGE_DOWN && y_vel > 0.0) {
+				shot.velocity[0] = 1.0 - shot.velocity[0];
+				bounced = true;
+			}
+			if bounced {
+				shot.velocity[0] = normalize_angle(shot.velocity[0]);
+				shot.behavior = Behavior::Bounce { times: times - 1 };
+			}
+			false
+		},
+	}
+}
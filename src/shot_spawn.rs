@@ -0,0 +1,122 @@
+use std::rc::Rc;
+
+use bullet_store::Bullet;
+use bullet_type::BulletStyle;
+use shot_behavior::Behavior;
+
+// 子弾の生成
+// 弾（親）が自分のcountや消えた時に子弾を生成する
+// 親は自分のspawnsだけを見るので, 他の弾には影響しない
+//
+// 子弾の角度は親の向き（velocity[0]）からの相対値
+// 子弾もspawnsを持てる（孫弾）
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+	At(u32),
+	// 親のcountがこの値の時
+	Every {
+		from: u32,
+		until: u32,
+		interval: u32,
+	},
+	// 親のcountが from ~ until の間 interval毎
+	Death,
+	// 親が画面外に出て消えた時
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChildPattern {
+	pub angles: Vec<f32>,
+	// 親の向きからの角度 1つにつき1発
+	pub speed: f32,
+	pub accel: [f32; 2],
+	// [角度, スカラ値]
+	pub style: BulletStyle,
+	pub behavior: Behavior,
+	pub children: Option<Rc<Vec<Spawn>>>,
+	// 子弾のspawns
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spawn {
+	pub trigger: Trigger,
+	pub pattern: Rc<ChildPattern>,
+	// 同じ子弾幕を多くの親で共有する
+	pub replace: bool,
+	// 生成した後に親を消す
+}
+
+impl Spawn {
+	pub fn new(trigger: Trigger, pattern: Rc<ChildPattern>, replace: bool) -> Spawn {
+		Spawn {
+			trigger,
+			pattern,
+			replace,
+		}
+	}
+}
+
+// 右へstep毎の角度と親の向き(0.0)を交互に並べる 0.0は段の数だけ重なる
+// split_angles(0.1, 1, 3) -> [0.1, 0.0, 0.2, 0.0]
+pub fn split_angles(step: f32, from: u32, to: u32) -> Vec<f32> {
+	let mut angles = Vec::new();
+	for i in from..to {
+		angles.push(step * i as f32);
+		angles.push(0.0);
+	}
+	angles
+}
+
+impl ChildPattern {
+	fn spawn(&self, parent: &Bullet, children: &mut Vec<Bullet>) {
+		for angle in &self.angles {
+			children.push(Bullet {
+				point: parent.point,
+				velocity: [parent.velocity[0] + angle, self.speed],
+				accel: self.accel,
+				style: self.style,
+				behavior: self.behavior,
+				spawns: self.children.clone(),
+				..Default::default()
+			});
+		}
+	}
+}
+
+// 親のcountに合わせて子弾をchildrenに追加
+pub fn update(shot: &mut Bullet, children: &mut Vec<Bullet>) {
+	let spawns = match shot.spawns {
+		Some(ref spawns) => spawns.clone(),
+		None => return,
+	};
+	let count = shot.count;
+	let mut replace = false;
+	for sp in spawns.iter() {
+		let fire = match sp.trigger {
+			Trigger::At(c) => c == count,
+			Trigger::Every { from, until, interval } => {
+				count >= from && count <= until && (count - from) % interval.max(1) == 0
+			},
+			Trigger::Death => false,
+		};
+		if fire {
+			sp.pattern.spawn(shot, children);
+			replace |= sp.replace;
+		}
+	}
+	if replace {
+		shot.alive = false;
+	}
+}
+
+// 親が画面外に出て消えた時の子弾をchildrenに追加
+pub fn on_death(shot: &Bullet, children: &mut Vec<Bullet>) {
+	if let Some(ref spawns) = shot.spawns {
+		for sp in spawns.iter() {
+			if sp.trigger == Trigger::Death {
+				sp.pattern.spawn(shot, children);
+			}
+		}
+	}
+}
@@ -1,7 +1,14 @@
-use Actor;
-use MovingElement;
-use rand::{Rng, ThreadRng};
+use {Actor, GameRng};
+use bullet_store::{Bullet, BulletStore};
+use bullet_type::{BulletStyle, BulletKind, BulletColor};
+use laser::Laser;
+use shot_behavior::Behavior;
+use shot_spawn::{split_angles, ChildPattern, Spawn, Trigger};
+use rand::Rng;
+use serde::{Deserialize, Deserializer};
+use serde::de;
 use std::f32;
+use std::rc::Rc;
 use std::f32::consts::PI;
 
 // Shot: [Angle(0.0 <= x < 2.0, 真下が0, 右回り), scalar]
@@ -15,6 +22,10 @@ use std::f32::consts::PI;
 // four_two_disp: ４方向＊２方向＊７発 角度; 自機, 乱数依存
 // m_six_rotate: ６方向＊連射 右回り
 // m_six_fireflower: ３００count周期＊６方向＊２度分裂
+// m_orbit_reaim:
+//   ８方向 周りを回って広がる -> 外側へ
+//   ４方向 止まってから自機狙い
+//   ２方向 自機追尾
 // b_caopet_bomb: １方向 角度; 乱数依存 カーブした後直線＊１２０C毎に子弾幕生成
 // b_normal: ８方向；乱数依存＊２連＊連射 アップグレード機能必要
 //
@@ -29,8 +40,112 @@ use std::f32::consts::PI;
 //
 // b_6carpet_fireflower:
 //   carpet＊６方向 -> fireflower＊速度,角度乱数
+//
+// b_laser_cage:
+//   自機狙いレーザー＊３本 予告線あり
+//   回転レーザー＊２本 左右逆回り
+//   曲がるレーザー＊３本＊左右交互
+//   １６方向 乱角度 合間に単発 壁で１回跳ね返る
+//
+// 弾の種類と色はBulletStyleで指定する（bullet_type.rs）
+// 撃った後の動きはBehaviorで指定する（shot_behavior.rs）
+// 分裂などの子弾はSpawnで指定する（shot_spawn.rs）
+//
+// story.json, boss.jsonのshot_typeはPatternに変換する
+// 登録されていない名前は読み込み時にエラー
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+	Six,
+	FourTwoDisp,
+	MSixRotate,
+	MSixFireflower,
+	MOrbitReaim,
+	BNormal,
+	B6Rotate4Rand,
+	B2Fireflower4Pdis,
+	B6CarpetFireflower,
+	BLaserCage,
+}
+
+impl Pattern {
+	pub const ALL: [Pattern; 10] = [
+		Pattern::Six,
+		Pattern::FourTwoDisp,
+		Pattern::MSixRotate,
+		Pattern::MSixFireflower,
+		Pattern::MOrbitReaim,
+		Pattern::BNormal,
+		Pattern::B6Rotate4Rand,
+		Pattern::B2Fireflower4Pdis,
+		Pattern::B6CarpetFireflower,
+		Pattern::BLaserCage,
+	];
+
+	// Jsonでの名前
+	pub fn name(self) -> &'static str {
+		match self {
+			Pattern::Six => "six",
+			Pattern::FourTwoDisp => "four-two_disp",
+			Pattern::MSixRotate => "m_six_rotate",
+			Pattern::MSixFireflower => "m_six_fireflower",
+			Pattern::MOrbitReaim => "m_orbit_reaim",
+			Pattern::BNormal => "b_normal",
+			Pattern::B6Rotate4Rand => "b_6rotate_4rand",
+			Pattern::B2Fireflower4Pdis => "b_2fireflower_4pdis",
+			Pattern::B6CarpetFireflower => "b_6carpet_fireflower",
+			Pattern::BLaserCage => "b_laser_cage",
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Pattern> {
+		Pattern::ALL.iter().cloned().find(|p| p.name() == name)
+	}
+}
 
-fn get_angle_from_points (p1: [f32; 2], p2: [f32; 2]) -> f32 {
+impl<'de> Deserialize<'de> for Pattern {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Pattern, D::Error> {
+		let name = String::deserialize(deserializer)?;
+		Pattern::from_name(&name)
+			.ok_or_else(|| de::Error::custom(format!("unknown shot_type \"{}\"", name)))
+	}
+}
+
+// "none", "" は弾幕なし
+pub fn deserialize_optional<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Pattern>, D::Error> {
+	let name = String::deserialize(deserializer)?;
+	match name.as_str() {
+		"none" | "" => Ok(None),
+		_ => Pattern::from_name(&name)
+			.map(Some)
+			.ok_or_else(|| de::Error::custom(format!("unknown shot_type \"{}\"", name))),
+	}
+}
+
+// patternの弾幕を撃つ
+pub fn fire(
+	pattern: Pattern,
+	enemy: &mut Actor,
+	p_point: [f32; 2],
+	en_shots: &mut BulletStore,
+	lasers: &mut Vec<Laser>,
+	count: u32,
+	rand: &mut GameRng) {
+	match pattern {
+		Pattern::Six => n_six(enemy, p_point, en_shots, count),
+		Pattern::FourTwoDisp => n_four_two_disp(enemy, p_point, en_shots, rand),
+		Pattern::MSixRotate => m_six_rotate(enemy, p_point, en_shots, count),
+		Pattern::MSixFireflower => m_six_fireflower(enemy, p_point, en_shots, count),
+		Pattern::MOrbitReaim => m_orbit_reaim(enemy, p_point, en_shots, count),
+		Pattern::BNormal => b_normal(enemy, p_point, en_shots, count, rand),
+		Pattern::B6Rotate4Rand => b_6rotate_4rand(enemy, p_point, en_shots, count, rand),
+		Pattern::B2Fireflower4Pdis => b_2fireflower_4pdis(enemy, p_point, en_shots, count, rand),
+		Pattern::B6CarpetFireflower => b_6carpet_fireflower(enemy, p_point, en_shots, count, rand),
+		Pattern::BLaserCage => b_laser_cage(enemy, p_point, en_shots, lasers, count, rand),
+	}
+}
+
+pub fn get_angle_from_points (p1: [f32; 2], p2: [f32; 2]) -> f32 {
 	//    p1
 	//   /|<- return angle
 	//  / |
@@ -41,7 +156,13 @@ fn get_angle_from_points (p1: [f32; 2], p2: [f32; 2]) -> f32 {
 	angle / PI
 }
 
-fn six_rotate(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut Vec<Actor>, count: u32, right: bool) {
+fn six_rotate(
+	enemy: &mut Actor,
+	p_point: [f32; 2],
+	en_shots: &mut BulletStore,
+	count: u32,
+	right: bool,
+	style: BulletStyle) {
 	for i in 0..6 {
 		let angle_plus = {
 			let round_per_sec = 1.0 / 30.0;
@@ -59,12 +180,17 @@ fn six_rotate(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut Vec<Actor>,
 		}
 		let sv = [angle, shot_scal];
 
-		en_shots.push(Actor::enemy_shot_new(ep, sv));
+		en_shots.push(Bullet::new(ep, sv, style));
 	}
 }
 
-fn four_two_disp(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut Vec<Actor>, rand: &ThreadRng) {
-	let r = rand.to_owned().gen::<f32>();
+fn four_two_disp(
+	enemy: &mut Actor,
+	p_point: [f32; 2],
+	en_shots: &mut BulletStore,
+	rand: &mut GameRng,
+	style: BulletStyle) {
+	let r = rand.gen::<f32>();
 	for i in 0..4 {
 		let ep = enemy.point;
 		let mut shot_scal = 120.0;
@@ -72,262 +198,322 @@ fn four_two_disp(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut Vec<Actor
 		let rand_angle = angle + 0.1 * (r - 0.5);
 		for j in 0..7 {
 			let sv = [rand_angle + 0.05, shot_scal + 30.0 * j as f32];
-			en_shots.push(Actor::enemy_shot_new(ep, sv));
+			en_shots.push(Bullet::new(ep, sv, style));
 			let sv = [rand_angle - 0.05, shot_scal + 30.0 * j as f32];
-			en_shots.push(Actor::enemy_shot_new(ep, sv));
+			en_shots.push(Bullet::new(ep, sv, style));
 		}
 	}
 }
 
+// origin -> 60count後に分裂 -> さらに60count後に分裂
 fn fireflower(
 	enemy: &mut Actor,
-	p_point: [f32; 2],
-	en_shots: &mut Vec<Actor>,
-	count: u32,
+	en_shots: &mut BulletStore,
 	shot_num: u32,
-	shot_angle: f32,
-	origin: bool) {
-	if origin {
-		// shot_timeの間隔でoriginshotを生成
-		for i in 0..shot_num {
-			let angle = (i as f32 + 2.0 * shot_angle) * 2.0 / shot_num as f32;
-
-			let push_shot = Actor {
-				point: enemy.point,
-				velocity: [angle, 300.0],
-				accel: [0.0, -300.0],
-				memo: "origin".to_owned(),
-				..Default::default()
-			};
-			en_shots.push(push_shot);
-		}
-	} else {
-		let mut push_enshot_buf: Vec<Actor> = Vec::new();
-		for es in &mut *en_shots {
-			match es.memo.as_str() {
-				"origin" => {
-					if es.count == 60{
-						// 分裂させる
-						es.memo = "origin_d".to_owned();
-						let mut push_shot = Actor {
-							point: es.point,
-							velocity: es.velocity,
-							memo: "split".to_owned(),
-							..Default::default()
-						};
-						for i in 0..4 {
-							push_shot.velocity[1] = 250.0;
-							push_shot.velocity[0] += 0.18 * i as f32;
-							push_enshot_buf.push(push_shot.clone());
-							push_shot.velocity[0] -= 0.18 * i as f32;
-							push_enshot_buf.push(push_shot.clone());
-						}
-					}
-				},
-				"split" => {
-					if es.count == 60 {
-						es.memo = "split_d".to_owned();
-						let mut push_shot = Actor {
-							point: es.point,
-							velocity: es.velocity,
-							memo: "".to_owned(),
-							..Default::default()
-						};
-						for i in 1..7 {
-							push_shot.velocity[1] = 250.0;
-							push_shot.velocity[0] += 0.18 * i as f32;
-							push_enshot_buf.push(push_shot.clone());
-							push_shot.velocity[0] -= 0.18 * i as f32;
-							push_enshot_buf.push(push_shot.clone());
-						}
-					}
-				},
-				_ => (),
-			}
-			if es.memo == "origin_d".to_owned() || es.memo == "split_d".to_owned() {
-				es.life = 0.0;
-			}
-		}
-		for peb in push_enshot_buf {
-			en_shots.push(peb);
-		}
+	shot_angle: f32) {
+	let last = Rc::new(ChildPattern {
+		angles: split_angles(0.18, 1, 7),
+		speed: 250.0,
+		accel: [0.0; 2],
+		style: BulletStyle::new(BulletKind::Pellet, BulletColor::Yellow),
+		behavior: Behavior::None,
+		children: None,
+	});
+	let split = Rc::new(ChildPattern {
+		angles: split_angles(0.18, 0, 4),
+		speed: 250.0,
+		accel: [0.0; 2],
+		style: BulletStyle::new(BulletKind::Star, BulletColor::Orange),
+		behavior: Behavior::None,
+		children: Some(Rc::new(vec![Spawn::new(Trigger::At(60), last, true)])),
+	});
+	let spawns = Rc::new(vec![Spawn::new(Trigger::At(60), split, true)]);
+
+	for i in 0..shot_num {
+		let angle = (i as f32 + 2.0 * shot_angle) * 2.0 / shot_num as f32;
+
+		let push_shot = Bullet {
+			point: enemy.point,
+			velocity: [angle, 300.0],
+			accel: [0.0, -300.0],
+			style: BulletStyle::new(BulletKind::Orb, BulletColor::Red),
+			spawns: Some(spawns.clone()),
+			..Default::default()
+		};
+		en_shots.push(push_shot);
 	}
 }
 
+// カーブしながら左右に子弾をばら撒く
 fn carpet_bomb(
 	enemy: &mut Actor,
-	p_point: [f32; 2],
-	en_shots: &mut Vec<Actor>,
+	en_shots: &mut BulletStore,
 	count: u32,
-	rand: &ThreadRng,
-	shot_origin: bool) {
-	if shot_origin {
-		let shot_scal = 200.0;
-		let angle = 2.0 * rand.to_owned().gen::<f32>();
+	rand: &mut GameRng) {
+	let shot_scal = 200.0;
+	let angle = 2.0 * rand.gen::<f32>();
 
-		let mut push_shot = Actor {
-			point: enemy.point,
-			velocity: [angle, shot_scal],
-			accel: [0.0, 0.0],
-			moving: vec![
-				MovingElement::new(30, [0.7, 0.0], "origin"),
-				MovingElement::new(120, [0.0, 0.0], ""),
-			],
-			memo: "".to_owned(),
-			..Default::default()
-		};
+	let child = Rc::new(ChildPattern {
+		angles: vec![0.7, -0.7],
+		speed: 250.0,
+		accel: [0.0; 2],
+		style: BulletStyle::new(BulletKind::Pellet, BulletColor::Green),
+		behavior: Behavior::None,
+		children: None,
+	});
+	// 曲がっている間, 弾幕のcountが5の倍数で40count周期の前半なら子弾を撒く
+	let spawns: Vec<Spawn> = (30..120)
+		.filter(|c| {
+			let rate = (count + c) % 500;
+			rate % 5 == 0 && rate % 40 < 20
+		})
+		.map(|c| Spawn::new(Trigger::At(c), child.clone(), false))
+		.collect();
+	let mut push_shot = Bullet {
+		point: enemy.point,
+		velocity: [angle, shot_scal],
+		accel: [0.0, 0.0],
+		behavior: Behavior::Curve { from: 30, until: 120, turn: 0.7 },
+		style: BulletStyle::new(BulletKind::Bubble, BulletColor::Green),
+		spawns: Some(Rc::new(spawns)),
+		..Default::default()
+	};
 
-		en_shots.push(push_shot.clone());
-		push_shot.velocity[0] += 2.0 / 3.0;
-		en_shots.push(push_shot.clone());
-		push_shot.velocity[0] += 2.0 / 3.0;
-		en_shots.push(push_shot.clone());
-	} else {
-		let split_rate = count % 120;
-		for es in en_shots.clone() {
-			// origin弾のみ弾幕を生成
-			match es.memo.as_str() {
-				"origin" => (),
-				_ => continue,
-			}
-			if count % 5 == 0 {
-				let mut push_shot = Actor {
-					point: es.point,
-					velocity: es.velocity,
-					memo: "".to_owned(),
-					..Default::default()
-				};
-				push_shot.velocity[1] = 250.0;
-				push_shot.velocity[0] += 0.7;
-				en_shots.push(push_shot.clone());
-				push_shot.velocity[0] -= 1.4;
-				en_shots.push(push_shot.clone());
-			}
-		}
-	}
+	en_shots.push(push_shot.clone());
+	push_shot.velocity[0] += 2.0 / 3.0;
+	en_shots.push(push_shot.clone());
+	push_shot.velocity[0] += 2.0 / 3.0;
+	en_shots.push(push_shot.clone());
 }
 
 
-pub fn n_six(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut Vec<Actor>, count: u32) {
+pub fn n_six(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut BulletStore, count: u32) {
 	for i in 0..6 {
 		let ep = enemy.point;
 		let mut shot_scal = 100.0;
 		let angle = (i as f32) / 3.0 + get_angle_from_points(enemy.point, p_point);
 
+		let style = BulletStyle::new(BulletKind::Pellet, BulletColor::Red);
 		for j in 1..=3 {
 			let sv = [angle, shot_scal + 40.0 * j as f32];
-			en_shots.push(Actor::enemy_shot_new(ep, sv));
+			en_shots.push(Bullet::new(ep, sv, style));
 		}
 
 		if count >= 3060 {
+			let style = BulletStyle::new(BulletKind::Pellet, BulletColor::Orange);
 			shot_scal += 60.0;
 			let sv = [angle + 0.12, shot_scal];
-			en_shots.push(Actor::enemy_shot_new(ep, sv));
+			en_shots.push(Bullet::new(ep, sv, style));
 			let sv = [angle - 0.12, shot_scal];
-			en_shots.push(Actor::enemy_shot_new(ep, sv));
+			en_shots.push(Bullet::new(ep, sv, style));
 
 			shot_scal += 30.0;
 			let sv = [angle + 1.32, shot_scal];
-			en_shots.push(Actor::enemy_shot_new(ep, sv));
+			en_shots.push(Bullet::new(ep, sv, style));
 			let sv = [angle - 1.32, shot_scal];
-			en_shots.push(Actor::enemy_shot_new(ep, sv));
+			en_shots.push(Bullet::new(ep, sv, style));
 
 		}
 	}
-	enemy.memo = String::new();
+	enemy.pattern = None;
 }
 
-pub fn n_four_two_disp(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut Vec<Actor>, rand: &ThreadRng) {
-	four_two_disp(enemy, p_point, en_shots, rand);
-	enemy.memo = String::new();
+pub fn n_four_two_disp(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut BulletStore, rand: &mut GameRng) {
+	let style = BulletStyle::new(BulletKind::Star, BulletColor::Cyan);
+	four_two_disp(enemy, p_point, en_shots, rand, style);
+	enemy.pattern = None;
 }
 
-pub fn m_six_rotate(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut Vec<Actor>, count: u32) {
+pub fn m_six_rotate(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut BulletStore, count: u32) {
 	if count > 50 && count % 3 == 0 {
-		six_rotate(enemy, p_point, en_shots, count, true);
+		let style = BulletStyle::new(BulletKind::Pellet, BulletColor::Blue);
+		six_rotate(enemy, p_point, en_shots, count, true, style);
 	}
 }
 
-pub fn m_six_fireflower(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut Vec<Actor>, count: u32) {
+pub fn m_six_fireflower(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut BulletStore, count: u32) {
 	// 5秒周期で射出
 	let shot_time = count % 300;
 	if count >= 50 && shot_time == 50 {
-		fireflower(enemy, p_point, en_shots, shot_time, 6, 0.0, true);
-	} else {
-		fireflower(enemy, p_point, en_shots, shot_time, 6, 0.0, false);
+		fireflower(enemy, en_shots, 6, 0.0);
+	}
+}
+
+pub fn m_orbit_reaim(enemy: &mut Actor, p_point: [f32; 2],  en_shots: &mut BulletStore, count: u32) {
+	let rate = count % 240;
+	let ep = enemy.point;
+	if rate == 30 || rate == 150 {
+		// 周りを回りながら広がる
+		let right = rate == 30;
+		for i in 0..8 {
+			let push_shot = Bullet {
+				point: ep,
+				velocity: [i as f32 / 4.0, 220.0],
+				behavior: Behavior::Orbit {
+					parent: enemy.id,
+					angular_speed: if right { 0.5 } else { -0.5 },
+					expand: 100.0,
+					duration: 90,
+				},
+				style: BulletStyle::new(BulletKind::Rice, BulletColor::Blue),
+				..Default::default()
+			};
+			en_shots.push(push_shot);
+		}
+	}
+	if rate % 60 == 0 {
+		// 止まってから自機狙い
+		for i in 0..4 {
+			let push_shot = Bullet {
+				point: ep,
+				velocity: [i as f32 / 2.0 + 0.25, 200.0],
+				accel: [0.0, -300.0],
+				behavior: Behavior::ReAim { delay: 40, speed: 280.0 },
+				style: BulletStyle::new(BulletKind::Knife, BulletColor::Red),
+				..Default::default()
+			};
+			en_shots.push(push_shot);
+		}
+	}
+	if rate == 90 {
+		// 自機追尾
+		for &angle in [0.4, -0.4].iter() {
+			let push_shot = Bullet {
+				point: ep,
+				velocity: [angle, 150.0],
+				behavior: Behavior::Homing { turn: 0.3, duration: 150 },
+				style: BulletStyle::new(BulletKind::Star, BulletColor::Yellow),
+				..Default::default()
+			};
+			en_shots.push(push_shot);
+		}
 	}
 }
 
 pub fn b_normal (
 	enemy: &mut Actor,
 	p_point: [f32; 2],
-	en_shots: &mut Vec<Actor>,
+	en_shots: &mut BulletStore,
 	count: u32,
-	rand: &ThreadRng) {
+	rand: &mut GameRng) {
 	let rate = count % 60;
 	if !(rate == 0 || rate == 20) {
 		return ();
 	}
 	let shot_n: u32 = 16;
-	let angle_rand = rand.to_owned().gen::<f32>();
+	let angle_rand = rand.gen::<f32>();
 	for i in 0..shot_n {
 		let ep = enemy.point;
 		let mut shot_scal = 100.0;
 		let angle = ((i as f32) + 2.0 * angle_rand) * 2.0 / shot_n as f32;
 
+		// 速い弾ほど明るい色
+		let colors = [BulletColor::Blue, BulletColor::Cyan, BulletColor::White];
 		for j in 1..=3 {
 			let sv = [angle, shot_scal + 40.0 * j as f32];
-			en_shots.push(Actor::enemy_shot_new(ep, sv));
+			let style = BulletStyle::new(BulletKind::Pellet, colors[j - 1]);
+			en_shots.push(Bullet::new(ep, sv, style));
 		}
 	}
 }
 pub fn b_6rotate_4rand(
 	enemy: &mut Actor,
 	p_point: [f32; 2],
-	en_shots: &mut Vec<Actor>,
+	en_shots: &mut BulletStore,
 	count: u32,
-	rand: &ThreadRng) {
+	rand: &mut GameRng) {
 		let rate = count % 350;
 		if rate % 5 == 0 && rate <= 90 {
-			six_rotate(enemy, p_point, en_shots, count, true);
+			let style = BulletStyle::new(BulletKind::Pellet, BulletColor::Red);
+			six_rotate(enemy, p_point, en_shots, count, true, style);
 		} else if rate % 5 == 0 && rate <= 200  {
 			if rate >= 110 {
-			six_rotate(enemy, p_point, en_shots, count, false);
+			let style = BulletStyle::new(BulletKind::Pellet, BulletColor::Blue);
+			six_rotate(enemy, p_point, en_shots, count, false, style);
 			}
 		} else if rate % 30 == 0 && rate <= 320 {
-			four_two_disp(enemy, p_point, en_shots, rand);
+			let style = BulletStyle::new(BulletKind::Star, BulletColor::Purple);
+			four_two_disp(enemy, p_point, en_shots, rand, style);
 		} else {}
 }
 
 pub fn b_2fireflower_4pdis(
 	enemy: &mut Actor,
 	p_point: [f32; 2],
-	en_shots: &mut Vec<Actor>,
+	en_shots: &mut BulletStore,
 	count: u32,
-	rand: &ThreadRng) {
+	rand: &mut GameRng) {
 		let rate = count % 300;
 		if rate % 30 == 0 && rate <= 90 {
-			let r = rand.to_owned().gen::<f32>();
-			fireflower(enemy, p_point, en_shots, rate, 2, r, true);
+			let r = rand.gen::<f32>();
+			fireflower(enemy, en_shots, 2, r);
 		} else if rate % 30 == 0 && rate <= 210 {
-			four_two_disp(enemy, p_point, en_shots, rand);
+			let style = BulletStyle::new(BulletKind::Star, BulletColor::Yellow);
+			four_two_disp(enemy, p_point, en_shots, rand, style);
 		}
-		fireflower(enemy, p_point, en_shots, rate, 2, 0.0, false);
 }
 
 pub fn b_6carpet_fireflower(
 	enemy: &mut Actor,
 	p_point: [f32; 2],
-	en_shots: &mut Vec<Actor>,
+	en_shots: &mut BulletStore,
 	count: u32,
-	rand: &ThreadRng) {
+	rand: &mut GameRng) {
 	let rate = count % 500;
 	if  rate % 3 == 0 && rate % 90 <= 60 && rate <= 240 {
-		six_rotate(enemy, p_point, en_shots, count, true);
+		let style = BulletStyle::new(BulletKind::Pellet, BulletColor::Purple);
+		six_rotate(enemy, p_point, en_shots, count, true, style);
 	} else if rate >= 250 && rate % 30 == 0 {
-		carpet_bomb(enemy, p_point, en_shots, count, rand, true);
+		carpet_bomb(enemy, en_shots, count, rand);
+	}
+}
+
+pub fn b_laser_cage(
+	enemy: &mut Actor,
+	p_point: [f32; 2],
+	en_shots: &mut BulletStore,
+	lasers: &mut Vec<Laser>,
+	count: u32,
+	rand: &mut GameRng) {
+	let rate = count % 360;
+	let ep = enemy.point;
+	if rate == 30 {
+		let angle = get_angle_from_points(enemy.point, p_point);
+		for i in 0..3 {
+			let a = angle + (i as f32 - 1.0) * 0.15;
+			lasers.push(Laser::straight(ep, a, 1200.0, 24.0, 60, 60, BulletColor::Blue));
+		}
+	} else if rate == 150 {
+		for &(a, speed) in [(0.25, 0.3), (-0.25, -0.3)].iter() {
+			let laser = Laser::straight(ep, a, 1200.0, 16.0, 40, 120, BulletColor::Red)
+				.with_angular_speed(speed);
+			lasers.push(laser);
+		}
+	}
+
+	if rate >= 210 && rate <= 300 && rate % 15 == 0 {
+		let right = (rate / 15) % 2 == 0;
+		let (angle, curve) = if right { (0.3, -0.5) } else { (-0.3, 0.5) };
+		for i in 0..3 {
+			let a = angle + curve * 0.2 * i as f32;
+			lasers.push(Laser::curvy(ep, [a, 280.0], [curve, 0.0], 40, 14.0, BulletColor::Purple));
+		}
 	}
-	if rate % 40 < 20 {
-		carpet_bomb(enemy, p_point, en_shots, count, rand, false);
+
+	if rate % 60 == 45 {
+		let shot_n: u32 = 16;
+		let angle_rand = rand.gen::<f32>();
+		let style = BulletStyle::new(BulletKind::Pellet, BulletColor::Cyan);
+		for i in 0..shot_n {
+			let angle = ((i as f32) + angle_rand) * 2.0 / shot_n as f32;
+			let push_shot = Bullet {
+				point: ep,
+				velocity: [angle, 160.0],
+				behavior: Behavior::Bounce { times: 1 },
+				style: style,
+				..Default::default()
+			};
+			en_shots.push(push_shot);
+		}
 	}
 }
@@ -0,0 +1,183 @@
+use ggez::graphics;
+use ggez::graphics::Drawable;
+use ggez::graphics::spritebatch::SpriteBatch;
+use ggez::{Context, GameResult};
+
+use std::collections::BTreeMap;
+
+// 弾, 敵などをSpriteBatchでまとめて描画する
+// 画像を登録してImageIdで指定し, add で1枚ずつ予約, draw_layer で層毎にまとめて描画する
+//
+// 同じ層の中は Blend(Alpha -> Add), 画像の登録順 で描画する
+// 同じBatchの中は add した順なので, 毎frame同じ順に add すれば描画の順も同じになる
+//
+// 画像の無い敵, Boss, 自機の弾は shapes の円, 四角で描画する
+
+pub type ImageId = usize;
+
+const SHAPE_SIZE: u16 = 32;
+// shapesの1つの大きさ (px)
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Blend {
+	Alpha,
+	Add,
+	// 加算 光る弾
+}
+
+impl Default for Blend {
+	fn default() -> Blend {
+		Blend::Alpha
+	}
+}
+
+impl Blend {
+	fn mode(self) -> graphics::BlendMode {
+		match self {
+			Blend::Alpha => graphics::BlendMode::Alpha,
+			Blend::Add => graphics::BlendMode::Add,
+		}
+	}
+}
+
+// 描画の順
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+	PlayerShots,
+	Enemies,
+	// Bossを含む
+	Items,
+	EnemyBullets,
+	Effects,
+}
+
+// shapesの画像
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+	Disc,
+	Square,
+}
+
+impl Shape {
+	fn src(self) -> graphics::Rect {
+		match self {
+			Shape::Disc => graphics::Rect::new(0.0, 0.0, 0.5, 1.0),
+			Shape::Square => graphics::Rect::new(0.5, 0.0, 0.5, 1.0),
+		}
+	}
+}
+
+// 左に白い円, 右に白い四角を並べた画像を作る
+fn shapes_image(ctx: &mut Context) -> GameResult<graphics::Image> {
+	let size = SHAPE_SIZE as usize;
+	let r = size as f32 / 2.0;
+	let mut rgba = Vec::with_capacity(size * 2 * size * 4);
+	for y in 0..size {
+		for x in 0..size * 2 {
+			let a = if x < size {
+				// 縁を1pxぼかす
+				let dx = x as f32 + 0.5 - r;
+				let dy = y as f32 + 0.5 - r;
+				let d = (dx * dx + dy * dy).sqrt();
+				((r - d).max(0.0).min(1.0) * 255.0) as u8
+			} else {
+				255
+			};
+			rgba.extend_from_slice(&[255, 255, 255, a]);
+		}
+	}
+	graphics::Image::from_rgba8(ctx, SHAPE_SIZE * 2, SHAPE_SIZE, &rgba)
+}
+
+// point を中心に大きさ size (px) の円か四角を描画するDrawParam
+pub fn shape_param(shape: Shape, point: [f32; 2], size: [f32; 2], rotation: f32, color: graphics::Color) -> graphics::DrawParam {
+	let s = SHAPE_SIZE as f32;
+	graphics::DrawParam {
+		src: shape.src(),
+		dest: graphics::Point2::new(point[0], point[1]),
+		rotation,
+		offset: graphics::Point2::new(0.5, 0.5),
+		scale: graphics::Point2::new(size[0] / s, size[1] / s),
+		color: Some(color),
+		..Default::default()
+	}
+}
+
+#[derive(Debug)]
+struct Batch {
+	batch: SpriteBatch,
+	len: usize,
+}
+
+#[derive(Debug)]
+pub struct SpriteRenderer {
+	images: Vec<graphics::Image>,
+	shapes: ImageId,
+	batches: BTreeMap<(Layer, Blend, ImageId), Batch>,
+	// 使い終わったBatchも次のframeで使うので残しておく
+}
+
+impl SpriteRenderer {
+	pub fn new(ctx: &mut Context) -> GameResult<SpriteRenderer> {
+		let shapes = shapes_image(ctx)?;
+		Ok(SpriteRenderer {
+			images: vec![shapes],
+			shapes: 0,
+			batches: BTreeMap::new(),
+		})
+	}
+
+	pub fn add_image(&mut self, image: graphics::Image) -> ImageId {
+		self.images.push(image);
+		self.images.len() - 1
+	}
+
+	pub fn image(&self, id: ImageId) -> &graphics::Image {
+		&self.images[id]
+	}
+
+	// shapesの画像 加算で描画する時に使う
+	pub fn shapes(&self) -> ImageId {
+		self.shapes
+	}
+
+	pub fn add(&mut self, layer: Layer, image: ImageId, blend: Blend, param: graphics::DrawParam) {
+		let images = &self.images;
+		let b = self.batches.entry((layer, blend, image)).or_insert_with(|| {
+			let mut batch = SpriteBatch::new(images[image].clone());
+			batch.set_blend_mode(Some(blend.mode()));
+			Batch { batch, len: 0 }
+		});
+		b.batch.add(param);
+		b.len += 1;
+	}
+
+	// point を中心に大きさ size (px) の円か四角
+	pub fn add_shape(
+		&mut self,
+		layer: Layer,
+		shape: Shape,
+		point: [f32; 2],
+		size: [f32; 2],
+		rotation: f32,
+		color: graphics::Color,
+	) {
+		let param = shape_param(shape, point, size, rotation, color);
+		let shapes = self.shapes;
+		self.add(layer, shapes, Blend::Alpha, param);
+	}
+
+	// 層の予約を全て描画して空にする
+	pub fn draw_layer(&mut self, ctx: &mut Context, layer: Layer) -> GameResult<()> {
+		for (_, b) in self.batches.iter_mut().filter(|&(k, _)| k.0 == layer) {
+			if b.len == 0 {
+				continue;
+			}
+			graphics::draw_ex(ctx, &b.batch, graphics::DrawParam::default())?;
+			b.batch.clear();
+			b.len = 0;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,378 @@
+use ggez::graphics;
+use ggez::{Context, GameResult};
+
+use std::path::PathBuf;
+
+use bindings::Action;
+use fonts::{Align, TextCache};
+use locale::Strings;
+use menu::{Menu, MenuEvent};
+use mode::{Character, Difficulty, PracticeStart, SpellStart};
+use replay::Replay;
+use score::HighScores;
+use {Section, RELATIVE_X, RELATIVE_Y};
+
+// Title画面のメニュー
+//
+// Main -+- Start ----> Difficulty -> Character -> 開始
+//       +- Practice -> Difficulty -> Character -> Stage -> Section -> Setup -> 開始
+//       +- Spell practice -> Difficulty -> Character -> Spell -> 開始
+//       +- Replay: replaysのリプレイを再生
+//       +- Scores: 難易度毎のハイスコア（左右で難易度, 上下で通常とPracticeを切り替え）
+//       +- Options: OptionsScreenを開く
+//       +- Quit
+//
+// Bomb, Pauseで1つ前のページへ戻る
+
+const MAIN_ITEMS: [&str; 7] = [
+	"title.start",
+	"title.practice",
+	"title.spell_practice",
+	"title.replay",
+	"title.scores",
+	"title.options",
+	"title.quit",
+];
+const STAGES: [&str; 1] = ["title.stage1"];
+const SETUP_ITEMS: [&str; 3] = ["setup.lives", "setup.bombs", "setup.start"];
+const SETUP_MAX: [u32; 2] = [9, 9];
+const SETUP_MIN: [u32; 2] = [1, 0];
+const SETUP_STEP: [u32; 2] = [1, 1];
+// Setupの左右で変える値 [Lives, Bombs, Power]
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TitleAction {
+	None,
+	Start {
+		difficulty: Difficulty,
+		character: Character,
+		practice: Option<PracticeStart>,
+		// Practiceの開始条件 スコアを別に記録する
+	},
+	Spell {
+		difficulty: Difficulty,
+		character: Character,
+		spell: SpellStart,
+	},
+	Replay(PathBuf),
+	Options,
+	Quit,
+}
+
+// Mainで選んだ遊び方
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flow {
+	Game,
+	Practice,
+	Spell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Page {
+	Main,
+	Difficulty,
+	Character,
+	Stage,
+	Section,
+	Setup,
+	Spell,
+	Replays,
+	Scores,
+}
+
+#[derive(Debug, Clone)]
+pub struct TitleScreen {
+	page: Page,
+	main: Menu,
+	difficulty: Menu,
+	character: Menu,
+	stage: Menu,
+	section: Menu,
+	sections: Vec<Section>,
+	// stage1の区切り sectionの項目と同じ順
+	setup: Menu,
+	setup_values: [u32; 2],
+	// [Lives, Bombs, Power]
+	spell: Menu,
+	spells: Vec<(SpellStart, String)>,
+	// [phase, 表示名] spellの項目と同じ順
+	replays: Menu,
+	replay_paths: Vec<PathBuf>,
+	// replaysの項目と同じ順
+	replay_dir: PathBuf,
+	flow: Flow,
+	score_page: usize,
+	// Scoresで表示しているDifficulty::ALLの番号
+	score_practice: bool,
+	// ScoresでPracticeのスコアを表示している
+}
+
+impl TitleScreen {
+	// spells: Spell practiceで選べるphase [phase, 表示名]
+	// replay_dir: Replayで一覧にするディレクトリ
+	pub fn new(sections: Vec<Section>, spells: Vec<(SpellStart, String)>, replay_dir: PathBuf) -> TitleScreen {
+		let difficulty: Vec<&str> = Difficulty::ALL.iter().map(|d| d.key()).collect();
+		let character: Vec<&str> = Character::ALL.iter().map(|c| c.key()).collect();
+		let section: Vec<&str> = sections.iter().map(|s| s.name.as_str()).collect();
+		let section = Menu::new(&section);
+		TitleScreen {
+			page: Page::Main,
+			main: Menu::new(&MAIN_ITEMS),
+			difficulty: Menu::new(&difficulty),
+			character: Menu::new(&character),
+			stage: Menu::new(&STAGES),
+			section,
+			sections,
+			setup: Menu::new(&SETUP_ITEMS),
+			setup_values: [3, 3],
+			spell: Menu::new(&[]),
+			spells,
+			replays: Menu::new(&[]),
+			replay_paths: Vec::new(),
+			replay_dir,
+			flow: Flow::Game,
+			score_page: 1,
+			score_practice: false,
+		}
+	}
+
+	// Title画面に戻った時
+	pub fn open(&mut self) {
+		self.page = Page::Main;
+	}
+
+	// 最初に選ぶ難易度（設定のDefault difficulty）
+	pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+		if let Some(i) = Difficulty::ALL.iter().position(|d| *d == difficulty) {
+			self.difficulty.set_cursor(i);
+		}
+	}
+
+	fn selected_start(&self) -> TitleAction {
+		let practice = if self.flow == Flow::Practice {
+			let section = &self.sections[self.section.cursor()];
+			Some(PracticeStart {
+				section: section.name.clone(),
+				count: section.count,
+				lives: self.setup_values[0],
+				bombs: self.setup_values[1],
+			})
+		} else {
+			None
+		};
+		TitleAction::Start {
+			difficulty: Difficulty::ALL[self.difficulty.cursor()],
+			character: Character::ALL[self.character.cursor()],
+			practice,
+		}
+	}
+
+	// 選んだ難易度の取得回数を表示する
+	fn open_spells(&mut self, scores: &HighScores) {
+		let d = Difficulty::ALL[self.difficulty.cursor()];
+		let labels = self.spells.iter()
+			.map(|&(ref sp, ref name)| {
+				let [captures, attempts] = scores.spell(sp, d);
+				format!("{}  {}/{}", name, captures, attempts)
+			})
+			.collect();
+		let cursor = self.spell.cursor();
+		self.spell.set_items(labels);
+		// 同じphaseを続けて選びやすくする
+		for _ in 0..cursor {
+			self.spell.down();
+		}
+		self.page = Page::Spell;
+	}
+
+	// 難易度の初期値からSetupを始める
+	fn open_setup(&mut self) {
+		let d = Difficulty::ALL[self.difficulty.cursor()];
+		self.setup_values = [d.lives() as u32, d.bombs()];
+		self.setup.reset();
+		self.set_setup_labels();
+		self.page = Page::Setup;
+	}
+
+	fn set_setup_labels(&mut self) {
+		for i in 0..SETUP_MAX.len() {
+			self.setup.set_value(i, format!("< {} >", self.setup_values[i]));
+		}
+	}
+
+	// Setupの値を左右で変える
+	fn adjust_setup(&mut self, action: Action) {
+		let i = self.setup.cursor();
+		if i >= SETUP_MAX.len() {
+			return;
+		}
+		let v = self.setup_values[i];
+		self.setup_values[i] = match action {
+			Action::Left => v.saturating_sub(SETUP_STEP[i]).max(SETUP_MIN[i]),
+			Action::Right => (v + SETUP_STEP[i]).min(SETUP_MAX[i]),
+			_ => v,
+		};
+		self.set_setup_labels();
+	}
+
+	fn open_replays(&mut self) {
+		self.replay_paths = Replay::list(&self.replay_dir);
+		let labels = self.replay_paths.iter()
+			.map(|p| p.file_stem().map_or(String::new(), |s| s.to_string_lossy().into_owned()))
+			.collect();
+		self.replays.set_items(labels);
+		self.page = Page::Replays;
+	}
+
+	pub fn input(&mut self, action: Action, scores: &HighScores) -> TitleAction {
+		if self.page == Page::Scores {
+			let n = Difficulty::ALL.len();
+			match action {
+				Action::Left => self.score_page = (self.score_page + n - 1) % n,
+				Action::Right => self.score_page = (self.score_page + 1) % n,
+				Action::Up | Action::Down => self.score_practice = !self.score_practice,
+				Action::Bomb | Action::Pause => self.page = Page::Main,
+				_ => (),
+			}
+			return TitleAction::None;
+		}
+		if self.page == Page::Setup {
+			self.adjust_setup(action);
+		}
+
+		let event = match self.page {
+			Page::Main => self.main.input(action),
+			Page::Difficulty => self.difficulty.input(action),
+			Page::Character => self.character.input(action),
+			Page::Stage => self.stage.input(action),
+			Page::Section => self.section.input(action),
+			Page::Setup => self.setup.input(action),
+			Page::Spell => self.spell.input(action),
+			Page::Replays => self.replays.input(action),
+			Page::Scores => MenuEvent::None,
+		};
+		match event {
+			MenuEvent::None => TitleAction::None,
+			MenuEvent::Confirm(i) => self.confirm(i, scores),
+			MenuEvent::Cancel => {
+				self.page = match self.page {
+					Page::Character => Page::Difficulty,
+					Page::Stage | Page::Spell => Page::Character,
+					Page::Section => Page::Stage,
+					Page::Setup => Page::Section,
+					_ => Page::Main,
+				};
+				TitleAction::None
+			},
+		}
+	}
+
+	fn confirm(&mut self, i: usize, scores: &HighScores) -> TitleAction {
+		match self.page {
+			Page::Main => {
+				match i {
+					0 | 1 | 2 => {
+						self.flow = [Flow::Game, Flow::Practice, Flow::Spell][i];
+						self.page = Page::Difficulty;
+					},
+					3 => self.open_replays(),
+					4 => self.page = Page::Scores,
+					5 => return TitleAction::Options,
+					_ => return TitleAction::Quit,
+				}
+				TitleAction::None
+			},
+			Page::Difficulty => {
+				self.page = Page::Character;
+				TitleAction::None
+			},
+			Page::Character => match self.flow {
+				Flow::Game => self.selected_start(),
+				Flow::Practice => {
+					self.page = Page::Stage;
+					TitleAction::None
+				},
+				Flow::Spell => {
+					self.open_spells(scores);
+					TitleAction::None
+				},
+			},
+			Page::Stage => {
+				self.page = Page::Section;
+				TitleAction::None
+			},
+			Page::Section => {
+				self.open_setup();
+				TitleAction::None
+			},
+			Page::Setup => {
+				if i < SETUP_MAX.len() {
+					return TitleAction::None;
+				}
+				self.selected_start()
+			},
+			Page::Spell => TitleAction::Spell {
+				difficulty: Difficulty::ALL[self.difficulty.cursor()],
+				character: Character::ALL[self.character.cursor()],
+				spell: self.spells[i].0.clone(),
+			},
+			Page::Replays => TitleAction::Replay(self.replay_paths[i].clone()),
+			Page::Scores => TitleAction::None,
+		}
+	}
+
+	pub fn draw(&self, ctx: &mut Context, scores: &HighScores, strings: &Strings, texts: &mut TextCache) -> GameResult<()> {
+		let mut text = |ctx: &mut Context, size: f32, s: &str, p: [f32; 2]| -> GameResult<()> {
+			texts.draw(ctx, s, (size * RELATIVE_X) as u32, [p[0] * RELATIVE_X, p[1] * RELATIVE_Y], Align::Left, None)
+		};
+
+		// Print "poi-Project"
+		text(ctx, 100.0, "poi-Project", [300.0, 200.0])?;
+
+		// Write "-"
+		graphics::rectangle(
+			ctx,
+			graphics::DrawMode::Fill,
+			graphics::Rect::new(
+				495.0 * RELATIVE_X,
+				280.0 * RELATIVE_Y,
+				40.0 * RELATIVE_X,
+				10.0 * RELATIVE_Y
+				),
+		)?;
+
+		let (heading, menu) = match self.page {
+			Page::Main => ("", &self.main),
+			Page::Difficulty => ("title.select_difficulty", &self.difficulty),
+			Page::Character => ("title.select_player", &self.character),
+			Page::Stage => ("title.practice", &self.stage),
+			Page::Section => ("title.select_section", &self.section),
+			Page::Setup => ("title.practice_setup", &self.setup),
+			Page::Spell => ("title.select_spell", &self.spell),
+			Page::Replays => ("title.replay", &self.replays),
+			Page::Scores => {
+				let d = Difficulty::ALL[self.score_page];
+				let kind = if self.score_practice { "scores.practice" } else { "scores.normal" };
+				text(ctx, 30.0, &format!("< {}: {} >", strings.get(kind), strings.get(d.key())), [420.0, 420.0])?;
+				for (i, e) in scores.top(d, self.score_practice).iter().enumerate() {
+					let note = match e.section {
+						Some(ref s) => s.as_str(),
+						None if e.cleared => strings.get("scores.clear"),
+						None => "",
+					};
+					let line = format!("{:>2}. {:>10}  {}  {}", i + 1, e.score, strings.get(e.character.key()), note);
+					text(ctx, 22.0, &line, [380.0, 480.0 + 36.0 * i as f32])?;
+				}
+				return Ok(());
+			},
+		};
+		if !heading.is_empty() {
+			text(ctx, 30.0, strings.get(heading), [420.0, 420.0])?;
+		}
+		if self.page == Page::Replays && menu.is_empty() {
+			text(ctx, 24.0, strings.get("title.no_replays"), [460.0, 480.0])?;
+		}
+		let size = (30.0 * RELATIVE_X) as u32;
+		menu.draw(ctx, strings, texts, [460.0 * RELATIVE_X, 480.0 * RELATIVE_Y], size, 50.0 * RELATIVE_Y)
+	}
+}
@@ -0,0 +1,25 @@
+extern crate poi_project;
+
+use poi_project::locale::Catalog;
+use poi_project::settings::Language;
+
+// 全ての言語のファイルに同じkeyがあり, "{}"の数が同じか確かめる
+#[test]
+fn catalogs_have_same_keys() {
+	let english = Catalog::load(&Catalog::path(Language::English)).unwrap();
+	for language in Language::ALL.iter() {
+		let catalog = Catalog::load(&Catalog::path(*language)).unwrap();
+		for key in english.keys() {
+			let s = catalog.get(key)
+				.unwrap_or_else(|| panic!("{}: \"{}\" is missing", language.code(), key));
+			assert_eq!(
+				s.matches("{}").count(),
+				english.get(key).unwrap().matches("{}").count(),
+				"{}: \"{}\" has a different number of {{}}", language.code(), key
+			);
+		}
+		for key in catalog.keys() {
+			assert!(english.get(key).is_some(), "{}: \"{}\" is not in en", language.code(), key);
+		}
+	}
+}
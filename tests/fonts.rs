@@ -0,0 +1,119 @@
+extern crate poi_project;
+extern crate serde_json;
+
+use poi_project::fonts::{needs_unicode, CHAIN};
+use poi_project::locale::Catalog;
+use poi_project::settings::Language;
+
+use serde_json::Value;
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+
+fn read(path: &str) -> Vec<u8> {
+	let mut f = File::open(path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+	let mut b = Vec::new();
+	f.read_to_end(&mut b).unwrap();
+	b
+}
+
+fn u16_at(b: &[u8], i: usize) -> u32 {
+	(b[i] as u32) << 8 | b[i + 1] as u32
+}
+
+fn u32_at(b: &[u8], i: usize) -> u32 {
+	u16_at(b, i) << 16 | u16_at(b, i + 2)
+}
+
+// TrueTypeのcmap（format 4, 12）からグリフのある文字を読む
+fn characters(b: &[u8]) -> HashSet<u32> {
+	let tables = u16_at(b, 4) as usize;
+	let cmap = (0..tables)
+		.map(|i| 12 + i * 16)
+		.find(|&r| &b[r..r + 4] == b"cmap")
+		.map(|r| u32_at(b, r + 8) as usize)
+		.expect("no cmap table");
+
+	let mut chars = HashSet::new();
+	for i in 0..u16_at(b, cmap + 2) as usize {
+		let sub = cmap + u32_at(b, cmap + 8 + i * 8) as usize;
+		match u16_at(b, sub) {
+			4 => {
+				let seg2 = u16_at(b, sub + 6) as usize;
+				let ends = sub + 14;
+				let starts = ends + seg2 + 2;
+				let deltas = starts + seg2;
+				let offsets = deltas + seg2;
+				for s in (0..seg2).step_by(2) {
+					let (start, end) = (u16_at(b, starts + s), u16_at(b, ends + s));
+					let (delta, offset) = (u16_at(b, deltas + s), u16_at(b, offsets + s) as usize);
+					for c in start..(end + 1).min(0xffff) {
+						let glyph = if offset == 0 {
+							(c + delta) & 0xffff
+						} else {
+							let g = u16_at(b, offsets + s + offset + (c - start) as usize * 2);
+							if g == 0 { 0 } else { (g + delta) & 0xffff }
+						};
+						if glyph != 0 {
+							chars.insert(c);
+						}
+					}
+				}
+			},
+			12 => {
+				for g in 0..u32_at(b, sub + 12) as usize {
+					let group = sub + 16 + g * 12;
+					chars.extend(u32_at(b, group)..u32_at(b, group + 4) + 1);
+				}
+			},
+			_ => {},
+		}
+	}
+	chars
+}
+
+// 画面に出す文字列: 全ての言語の名前と文字列, story.jsonの会話
+fn shown_strings() -> Vec<String> {
+	let mut strings = Vec::new();
+	for language in Language::ALL.iter() {
+		strings.push(language.name().to_owned());
+		let catalog = Catalog::load(&Catalog::path(*language)).unwrap();
+		for key in catalog.keys() {
+			strings.push(catalog.get(key).unwrap().replace("{}", ""));
+		}
+	}
+	let story: Value = serde_json::from_str(&String::from_utf8(read("resources/story.json")).unwrap()).unwrap();
+	if let Some(stages) = story["dialogue"].as_object() {
+		for scenes in stages.values().filter_map(|s| s.as_object()) {
+			for line in scenes.values().filter_map(|s| s.as_array()).flat_map(|s| s.iter()) {
+				for field in &["speaker", "text"] {
+					if let Some(s) = line[*field].as_str() {
+						strings.push(s.replace('\n', ""));
+					}
+				}
+			}
+		}
+	}
+	strings
+}
+
+// CHAINのフォントが同梱されていて, ASCII以外の文字を含む文字列を
+// font_forが選ぶフォント（ASCII以外も表示できる最初のフォント）で全て表示できるか確かめる
+// ASCIIだけの文字列はLATIN_FONTで表示する（元のHUDと同じ）
+#[test]
+fn chain_can_display_every_string() {
+	let fonts: Vec<(&str, bool, HashSet<u32>)> = CHAIN.iter()
+		.map(|&(path, has_unicode)| (path, has_unicode, characters(&read(&format!("resources{}", path)))))
+		.collect();
+
+	for s in shown_strings().iter().filter(|s| needs_unicode(s)) {
+		let &(path, _, ref chars) = fonts.iter()
+			.find(|f| f.1)
+			.unwrap_or_else(|| panic!("\"{}\": no font in the chain can display non-ASCII text", s));
+		let missing: String = s.chars()
+			.filter(|c| !c.is_whitespace() && !chars.contains(&(*c as u32)))
+			.collect();
+		assert!(missing.is_empty(), "\"{}\": {} has no glyph for \"{}\"", s, path, missing);
+	}
+}
@@ -0,0 +1,61 @@
+extern crate poi_project;
+
+use poi_project::settings::{Settings, MAX_VOLUME};
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+// テスト毎の一時ディレクトリにsettings.jsonを書く
+fn write_settings(name: &str, json: &str) -> PathBuf {
+	let dir = env::temp_dir().join(format!("poi-settings-{}-{}", name, process::id()));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+	let path = dir.join("settings.json");
+	fs::write(&path, json).unwrap();
+	path
+}
+
+#[test]
+fn missing_file_is_default() {
+	let path = env::temp_dir().join(format!("poi-settings-none-{}", process::id())).join("settings.json");
+	assert_eq!(Settings::load(&path), Settings::default());
+}
+
+#[test]
+fn corrupt_json_is_default_and_backed_up() {
+	let path = write_settings("corrupt", "{\"version\": 3, \"bgm_volume\": ");
+	assert_eq!(Settings::load(&path), Settings::default());
+	assert!(!path.exists());
+	assert!(path.with_extension("json.bak").exists());
+}
+
+#[test]
+fn newer_version_is_default_and_backed_up() {
+	let path = write_settings("newer", "{\"version\": 999, \"bgm_volume\": 10}");
+	assert_eq!(Settings::load(&path), Settings::default());
+	assert!(!path.exists());
+	assert!(path.with_extension("json.bak").exists());
+}
+
+#[test]
+fn missing_field_keeps_the_others() {
+	let path = write_settings("missing", "{\"version\": 2, \"bgm_volume\": 30, \"fullscreen\": true}");
+	let settings = Settings::load(&path);
+	let default = Settings::default();
+	assert_eq!(settings.bgm_volume, 30);
+	assert!(settings.fullscreen);
+	assert_eq!(settings.se_volume, default.se_volume);
+	assert_eq!(settings.screen_effects, default.screen_effects);
+	assert_eq!(settings.bindings, default.bindings);
+}
+
+#[test]
+fn out_of_range_values_are_clamped() {
+	let path = write_settings("range", "{\"version\": 3, \"bgm_volume\": 500, \"se_volume\": 101, \"window_scale\": 3.0}");
+	let settings = Settings::load(&path);
+	assert_eq!(settings.bgm_volume, MAX_VOLUME);
+	assert_eq!(settings.se_volume, MAX_VOLUME);
+	assert_eq!(settings.window_scale, 1.0);
+}